@@ -0,0 +1,111 @@
+use std::io;
+use std::process::{Command, ExitStatus, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Turn on logging of every external command wt runs (`-v`).
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+fn verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Run `cmd` to completion, capturing its output. With `-v`, logs the argv
+/// and cwd, the exit status, and stderr when it fails.
+pub fn run_command(cmd: &mut Command) -> io::Result<Output> {
+    if !verbose() {
+        return cmd.output();
+    }
+
+    eprintln!("wt: $ {}", describe(cmd));
+    let result = cmd.output();
+    match &result {
+        Ok(output) if output.status.success() => eprintln!("wt:   {}", output.status),
+        Ok(output) => eprintln!(
+            "wt:   {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        ),
+        Err(error) => eprintln!("wt:   failed to start: {}", error),
+    }
+    result
+}
+
+/// Run `cmd` with inherited stdio (shells, editors, `tmux attach`). With
+/// `-v`, logs the argv, cwd and exit status.
+pub fn run_status(cmd: &mut Command) -> io::Result<ExitStatus> {
+    if !verbose() {
+        return cmd.status();
+    }
+
+    eprintln!("wt: $ {}", describe(cmd));
+    let result = cmd.status();
+    match &result {
+        Ok(status) => eprintln!("wt:   {}", status),
+        Err(error) => eprintln!("wt:   failed to start: {}", error),
+    }
+    result
+}
+
+/// Method-call form of `run_command` / `run_status`, so builder chains only
+/// change in their last call.
+pub trait CommandExt {
+    fn run(&mut self) -> io::Result<Output>;
+    fn run_status(&mut self) -> io::Result<ExitStatus>;
+}
+
+impl CommandExt for Command {
+    fn run(&mut self) -> io::Result<Output> {
+        run_command(self)
+    }
+
+    fn run_status(&mut self) -> io::Result<ExitStatus> {
+        run_status(self)
+    }
+}
+
+/// `program arg...`, plus ` (in <dir>)` when a cwd is set.
+fn describe(cmd: &Command) -> String {
+    let mut line = cmd.get_program().to_string_lossy().into_owned();
+    for arg in cmd.get_args() {
+        let arg = arg.to_string_lossy();
+        if arg.is_empty() || arg.contains(char::is_whitespace) {
+            line.push_str(&format!(" '{}'", arg));
+        } else {
+            line.push(' ');
+            line.push_str(&arg);
+        }
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        line.push_str(&format!(" (in {})", dir.display()));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_includes_args_and_cwd() {
+        let mut cmd = Command::new("git");
+        cmd.args(["commit", "-m", "two words", ""])
+            .current_dir("/tmp/repo");
+
+        assert_eq!(
+            describe(&cmd),
+            "git commit -m 'two words' '' (in /tmp/repo)"
+        );
+        assert_eq!(describe(&Command::new("tmux")), "tmux");
+    }
+
+    #[test]
+    fn test_run_command_returns_output() {
+        let output = Command::new("git").arg("--version").run().unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).starts_with("git version"));
+    }
+}
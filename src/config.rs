@@ -1,7 +1,8 @@
 use anyhow::Result;
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum, Default)]
 #[serde(rename_all = "lowercase")]
@@ -13,8 +14,16 @@ pub enum SessionMode {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
+    /// Where worktrees are created, relative to the repo root (or
+    /// absolute). The `-d` flag overrides it; `.worktrees` when unset.
+    #[serde(default)]
+    pub worktree_dir: Option<PathBuf>,
     #[serde(default)]
     pub session: SessionConfig,
+    #[serde(default)]
+    pub shell: ShellConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +38,54 @@ pub struct SessionConfig {
     pub agent_cmd: String,
     #[serde(default = "default_editor_cmd")]
     pub editor_cmd: String,
+    /// Text that appears in an agent's pane once it is ready for input,
+    /// keyed by the agent's program name (e.g. `claude`).
+    #[serde(default)]
+    pub ready_patterns: HashMap<String, String>,
+    #[serde(default = "default_ready_timeout_secs")]
+    pub ready_timeout_secs: u64,
+    /// Command run by `wt session watch --notify` when an agent goes from
+    /// active to idle; the window name is in `$WT_WINDOW`.
+    #[serde(default)]
+    pub notify_cmd: Option<String>,
+    /// `wt session watch` flags a running agent as stalled once its window
+    /// has printed nothing for this many seconds.
+    #[serde(default = "default_stall_after_secs")]
+    pub stall_after_secs: u64,
+    /// Append everything the agent pane prints to `.wt-agent.log` in the
+    /// worktree, so it outlives tmux scrollback and the window.
+    #[serde(default)]
+    pub log_agent_output: bool,
+    /// Arrangement applied after splitting a worktree window: a tmux layout
+    /// name like `main-vertical`, or `N%` for the agent pane's width.
+    #[serde(default)]
+    pub layout: Option<String>,
+    /// Agent pane width as a percent of the window, used when no `layout`
+    /// is set. Clamped to 20-80; the default 50 keeps tmux's even split.
+    #[serde(default = "default_main_pane_percent")]
+    pub main_pane_percent: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellConfig {
+    /// Prefix prepended to the subshell prompt. Supports `{name}`,
+    /// `{branch}` and `{path}` placeholders; empty disables the indicator.
+    #[serde(default = "default_prompt_template")]
+    pub prompt_template: String,
+    /// Command run in the subshell after the user's rc files load, written
+    /// in that shell's syntax (e.g. `source .venv/bin/activate`).
+    #[serde(default)]
+    pub init: Option<String>,
+}
+
+/// Shell commands run at points in a worktree's lifecycle, via `sh -c`
+/// with `WT_NAME` and `WT_PATH` set.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HooksConfig {
+    /// Run in the worktree before `wt rm` deletes it. A non-zero exit
+    /// aborts the removal unless `--force` is passed.
+    #[serde(default)]
+    pub pre_remove: Option<String>,
 }
 
 fn default_panes() -> u8 {
@@ -47,6 +104,22 @@ fn default_editor_cmd() -> String {
     "nvim".to_string()
 }
 
+fn default_ready_timeout_secs() -> u64 {
+    30
+}
+
+fn default_stall_after_secs() -> u64 {
+    300
+}
+
+fn default_main_pane_percent() -> u8 {
+    50
+}
+
+fn default_prompt_template() -> String {
+    "(wt) ".to_string()
+}
+
 impl Default for SessionConfig {
     fn default() -> Self {
         Self {
@@ -55,6 +128,13 @@ impl Default for SessionConfig {
             session_prefix: default_session_prefix(),
             agent_cmd: default_agent_cmd(),
             editor_cmd: default_editor_cmd(),
+            ready_patterns: HashMap::new(),
+            ready_timeout_secs: default_ready_timeout_secs(),
+            notify_cmd: None,
+            stall_after_secs: default_stall_after_secs(),
+            log_agent_output: false,
+            layout: None,
+            main_pane_percent: default_main_pane_percent(),
         }
     }
 }
@@ -66,18 +146,61 @@ impl SessionConfig {
     pub fn session_name_for(&self, worktree: &str) -> String {
         format!("{}{}", self.session_prefix, worktree)
     }
+
+    /// Program name of `agent_cmd`: the file name of its first token.
+    pub fn agent_program(&self) -> Option<&str> {
+        let program = self.agent_cmd.split_whitespace().next()?;
+        Some(
+            Path::new(program)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(program),
+        )
+    }
+
+    /// Look up the configured readiness pattern for `agent_cmd`, keyed by
+    /// its program name.
+    pub fn ready_pattern(&self) -> Option<&str> {
+        self.ready_patterns
+            .get(self.agent_program()?)
+            .map(String::as_str)
+    }
+
+    /// `main_pane_percent` clamped to a width that leaves both panes usable.
+    pub fn effective_main_pane_percent(&self) -> u8 {
+        self.main_pane_percent.clamp(20, 80)
+    }
+}
+
+impl Default for ShellConfig {
+    fn default() -> Self {
+        Self {
+            prompt_template: default_prompt_template(),
+            init: None,
+        }
+    }
+}
+
+impl ShellConfig {
+    /// Expand `prompt_template` for a specific worktree.
+    pub fn render_prompt(&self, name: &str, branch: &str, path: &Path) -> String {
+        self.prompt_template
+            .replace("{name}", name)
+            .replace("{branch}", branch)
+            .replace("{path}", &path.display().to_string())
+    }
 }
 
 impl Config {
-    /// Load config with precedence: .wt.toml > ~/.wt/config.toml > defaults
+    /// Load config with precedence: .wt.toml > global config.toml > defaults
     pub fn load() -> Self {
-        let global = dirs::home_dir().map(|home| home.join(".wt").join("config.toml"));
+        let global = Self::config_dir().map(|dir| dir.join("config.toml"));
         Self::load_layered(global.as_deref(), Some(Path::new(".wt.toml")))
     }
 
     /// Load config for a specific repo path
     pub fn load_for_repo(repo_path: &Path) -> Self {
-        let global = dirs::home_dir().map(|home| home.join(".wt").join("config.toml"));
+        let global = Self::config_dir().map(|dir| dir.join("config.toml"));
         let local = repo_path.join(".wt.toml");
         Self::load_layered(global.as_deref(), Some(&local))
     }
@@ -105,16 +228,41 @@ impl Config {
 
     /// Get effective pane count (flag override if provided)
     pub fn effective_panes(&self, flag_override: Option<u8>) -> u8 {
-        flag_override.unwrap_or(self.session.panes).clamp(2, 3)
+        flag_override.unwrap_or(self.session.panes).clamp(1, 3)
+    }
+
+    /// Directory holding the global `config.toml`: `$XDG_CONFIG_HOME/wt`,
+    /// or the legacy `~/.wt` when that already exists.
+    pub fn config_dir() -> Option<PathBuf> {
+        resolve_wt_dir(legacy_wt_dir(), dirs::config_dir())
+    }
+
+    /// Directory holding session state (`sessions/`): `$XDG_DATA_HOME/wt`,
+    /// or the legacy `~/.wt` when that already exists.
+    pub fn data_dir() -> Option<PathBuf> {
+        resolve_wt_dir(legacy_wt_dir(), dirs::data_dir())
+    }
+
+    /// Ensure the data directory exists and return it.
+    pub fn ensure_data_dir() -> Result<PathBuf> {
+        let data_dir =
+            Self::data_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+        std::fs::create_dir_all(&data_dir)?;
+        Ok(data_dir)
     }
+}
+
+fn legacy_wt_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".wt"))
+}
 
-    /// Ensure ~/.wt directory exists
-    pub fn ensure_wt_dir() -> Result<std::path::PathBuf> {
-        let home =
-            dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
-        let wt_dir = home.join(".wt");
-        std::fs::create_dir_all(&wt_dir)?;
-        Ok(wt_dir)
+/// Prefer an existing legacy `~/.wt` so upgrading doesn't strand config or
+/// state; otherwise use `wt` under the platform base dir.
+fn resolve_wt_dir(legacy: Option<PathBuf>, base: Option<PathBuf>) -> Option<PathBuf> {
+    match (legacy, base) {
+        (Some(legacy), _) if legacy.is_dir() => Some(legacy),
+        (_, Some(base)) => Some(base.join("wt")),
+        (legacy, None) => legacy,
     }
 }
 
@@ -179,13 +327,27 @@ mod tests {
     #[test]
     fn test_effective_panes_clamp() {
         let config = Config::default();
-        assert_eq!(config.effective_panes(Some(1)), 2);
+        assert_eq!(config.effective_panes(Some(0)), 1);
+        assert_eq!(config.effective_panes(Some(1)), 1);
         assert_eq!(config.effective_panes(Some(2)), 2);
         assert_eq!(config.effective_panes(Some(3)), 3);
         assert_eq!(config.effective_panes(Some(4)), 3);
         assert_eq!(config.effective_panes(None), 2);
     }
 
+    #[test]
+    fn test_main_pane_percent_default_and_clamp() {
+        let mut config: Config = toml::from_str("[session]\nmain_pane_percent = 65\n").unwrap();
+        assert_eq!(config.session.effective_main_pane_percent(), 65);
+
+        config.session.main_pane_percent = 95;
+        assert_eq!(config.session.effective_main_pane_percent(), 80);
+        config.session.main_pane_percent = 0;
+        assert_eq!(config.session.effective_main_pane_percent(), 20);
+
+        assert_eq!(Config::default().session.effective_main_pane_percent(), 50);
+    }
+
     #[test]
     fn test_parse_toml() {
         let toml_str = r#"
@@ -212,6 +374,16 @@ panes = 3
         assert_eq!(config.session.editor_cmd, "nvim");
     }
 
+    #[test]
+    fn test_parse_worktree_dir() {
+        let config: Config = toml::from_str("worktree_dir = \"../.worktrees-myrepo\"\n").unwrap();
+        assert_eq!(
+            config.worktree_dir,
+            Some(PathBuf::from("../.worktrees-myrepo"))
+        );
+        assert_eq!(Config::default().worktree_dir, None);
+    }
+
     #[test]
     fn test_default_mode_is_panes() {
         let config = Config::default();
@@ -287,6 +459,140 @@ session_prefix = ""
         assert_eq!(config.session.session_prefix, "");
     }
 
+    #[test]
+    fn test_ready_pattern_matches_agent_program_name() {
+        let toml_str = r#"
+[session]
+agent_cmd = "/usr/local/bin/aider --model sonnet"
+
+[session.ready_patterns]
+aider = "> "
+claude = "? for shortcuts"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.session.ready_pattern(), Some("> "));
+        assert_eq!(config.session.ready_timeout_secs, 30);
+    }
+
+    #[test]
+    fn test_agent_program_strips_path_and_args() {
+        let mut config = SessionConfig {
+            agent_cmd: "/usr/local/bin/claude --resume".to_string(),
+            ..SessionConfig::default()
+        };
+        assert_eq!(config.agent_program(), Some("claude"));
+
+        config.agent_cmd = "  ".to_string();
+        assert_eq!(config.agent_program(), None);
+    }
+
+    #[test]
+    fn test_ready_pattern_missing_for_unconfigured_agent() {
+        let config = Config::default();
+        assert_eq!(config.session.ready_pattern(), None);
+    }
+
+    #[test]
+    fn test_parse_notify_cmd() {
+        let toml_str = r#"
+[session]
+notify_cmd = "notify-send wt \"$WT_WINDOW is idle\""
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.session.notify_cmd.as_deref(),
+            Some("notify-send wt \"$WT_WINDOW is idle\"")
+        );
+        assert_eq!(Config::default().session.notify_cmd, None);
+    }
+
+    #[test]
+    fn test_default_prompt_template() {
+        let config = Config::default();
+        assert_eq!(
+            config
+                .shell
+                .render_prompt("feature/auth", "feature/auth", Path::new("/tmp/wt")),
+            "(wt) "
+        );
+    }
+
+    #[test]
+    fn test_render_prompt_expands_placeholders() {
+        let toml_str = r#"
+[shell]
+prompt_template = "[{name}@{branch} {path}] "
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config
+                .shell
+                .render_prompt("auth", "feature/auth", Path::new("/tmp/auth")),
+            "[auth@feature/auth /tmp/auth] "
+        );
+    }
+
+    #[test]
+    fn test_empty_prompt_template_disables_indicator() {
+        let toml_str = r#"
+[shell]
+prompt_template = ""
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config
+                .shell
+                .render_prompt("auth", "auth", Path::new("/tmp/auth")),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_parse_shell_init() {
+        let toml_str = r#"
+[shell]
+init = "source .venv/bin/activate"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.shell.init.as_deref(),
+            Some("source .venv/bin/activate")
+        );
+        assert_eq!(config.shell.prompt_template, "(wt) ");
+        assert!(Config::default().shell.init.is_none());
+    }
+
+    #[test]
+    fn test_resolve_wt_dir_prefers_existing_legacy_dir() {
+        let home = tempfile::tempdir().unwrap();
+        let legacy = home.path().join(".wt");
+        std::fs::create_dir(&legacy).unwrap();
+        let xdg = home.path().join(".config");
+
+        assert_eq!(
+            resolve_wt_dir(Some(legacy.clone()), Some(xdg)),
+            Some(legacy)
+        );
+    }
+
+    #[test]
+    fn test_resolve_wt_dir_uses_xdg_without_legacy_dir() {
+        let home = tempfile::tempdir().unwrap();
+        let legacy = home.path().join(".wt");
+        let xdg = home.path().join(".local").join("share");
+
+        assert_eq!(
+            resolve_wt_dir(Some(legacy), Some(xdg.clone())),
+            Some(xdg.join("wt"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_wt_dir_falls_back_to_legacy_without_base() {
+        let legacy = PathBuf::from("/nonexistent/home/.wt");
+        assert_eq!(resolve_wt_dir(Some(legacy.clone()), None), Some(legacy));
+    }
+
     #[test]
     fn test_deep_merge_tables_preserves_unshadowed_keys() {
         let mut base: toml::Table = toml::from_str(
@@ -0,0 +1,260 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use wt::command::CommandExt;
+use wt::config::Config;
+use wt::git::Git;
+use wt::hooks::is_executable;
+use wt::tmux_manager::TmuxManager;
+
+/// `git worktree move` and `remove` were added in git 2.17.
+const MIN_GIT_VERSION: (u32, u32) = (2, 17);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CheckStatus {
+    Pass,
+    /// Optional feature unavailable; core commands still work.
+    Warn,
+    /// Hard requirement missing; wt will not work.
+    Fail,
+}
+
+struct Check {
+    status: CheckStatus,
+    message: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn pass(message: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Pass,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+pub(crate) fn run_doctor(repo: Option<&Path>) -> Result<()> {
+    let repo_root = repo_root(repo);
+    let config = match &repo_root {
+        Some(root) => Config::load_for_repo(root),
+        None => Config::load(),
+    };
+
+    let checks = [
+        check_git(),
+        check_repo(repo_root.as_deref()),
+        check_tmux(),
+        check_command("agent_cmd", &config.session.agent_cmd),
+        check_command("editor_cmd", &config.session.editor_cmd),
+        check_data_dir(),
+    ];
+
+    for check in &checks {
+        let label = match check.status {
+            CheckStatus::Pass => "\x1B[32m[ok]\x1B[0m  ",
+            CheckStatus::Warn => "\x1B[33m[warn]\x1B[0m",
+            CheckStatus::Fail => "\x1B[31m[fail]\x1B[0m",
+        };
+        println!("{} {}", label, check.message);
+        if let Some(hint) = &check.hint {
+            println!("       {}", hint);
+        }
+    }
+
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{} required check(s) failed", failed);
+    }
+    Ok(())
+}
+
+fn repo_root(repo: Option<&Path>) -> Option<PathBuf> {
+    Git::new(repo.unwrap_or(Path::new(".")))
+        .show_toplevel()
+        .ok()
+}
+
+fn check_git() -> Check {
+    let Ok(output) = Command::new("git").arg("--version").run() else {
+        return Check::fail(
+            "git not found",
+            "Install git and make sure it is on your PATH.",
+        );
+    };
+
+    let version_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match parse_git_version(&version_str) {
+        Some(version) if version >= MIN_GIT_VERSION => Check::pass(version_str),
+        Some(_) => Check::fail(
+            format!("{} is too old", version_str),
+            format!(
+                "Upgrade git to {}.{} or newer for full worktree support.",
+                MIN_GIT_VERSION.0, MIN_GIT_VERSION.1
+            ),
+        ),
+        None => Check::warn(
+            format!("could not parse git version from '{}'", version_str),
+            format!(
+                "wt needs git {}.{} or newer.",
+                MIN_GIT_VERSION.0, MIN_GIT_VERSION.1
+            ),
+        ),
+    }
+}
+
+fn check_repo(repo_root: Option<&Path>) -> Check {
+    match repo_root {
+        Some(root) => Check::pass(format!("inside git repository {}", root.display())),
+        None => Check::fail(
+            "current directory is not a git repository",
+            "Run wt from inside a git checkout.",
+        ),
+    }
+}
+
+fn check_tmux() -> Check {
+    if TmuxManager::is_available() {
+        Check::pass("tmux available")
+    } else {
+        Check::warn(
+            "tmux not found",
+            "Install tmux to use 'wt session'; other commands work without it.",
+        )
+    }
+}
+
+fn check_command(key: &str, command: &str) -> Check {
+    let Some(program) = command.split_whitespace().next() else {
+        return Check::warn(
+            format!("{} is empty", key),
+            format!("Set session.{} in your config.", key),
+        );
+    };
+
+    match find_program(program, std::env::var_os("PATH").as_deref()) {
+        Some(path) => Check::pass(format!("{} '{}' found at {}", key, program, path.display())),
+        None => Check::warn(
+            format!("{} '{}' not found on PATH", key, program),
+            format!(
+                "Install '{}' or set session.{} in .wt.toml / the global config.toml.",
+                program, key
+            ),
+        ),
+    }
+}
+
+fn check_data_dir() -> Check {
+    let data_dir = match Config::ensure_data_dir() {
+        Ok(dir) => dir,
+        Err(error) => {
+            return Check::warn(
+                format!("could not create wt data directory: {}", error),
+                "Session state cannot be saved; check your home directory permissions.",
+            )
+        }
+    };
+
+    let probe = data_dir.join(".doctor-write-test");
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            Check::pass(format!("{} is writable", data_dir.display()))
+        }
+        Err(error) => Check::warn(
+            format!("{} is not writable: {}", data_dir.display(), error),
+            "Session state cannot be saved; fix the directory permissions.",
+        ),
+    }
+}
+
+/// Parse `(major, minor)` out of `git --version` output such as
+/// `git version 2.39.3 (Apple Git-146)`.
+fn parse_git_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.strip_prefix("git version ")?;
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Resolve `program` the way a shell would: paths containing `/` are
+/// checked directly, bare names are searched for on `path_var`.
+fn find_program(program: &str, path_var: Option<&std::ffi::OsStr>) -> Option<PathBuf> {
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return is_executable(&path).then_some(path);
+    }
+
+    std::env::split_paths(path_var?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_version() {
+        assert_eq!(parse_git_version("git version 2.43.0"), Some((2, 43)));
+        assert_eq!(
+            parse_git_version("git version 2.39.3 (Apple Git-146)"),
+            Some((2, 39))
+        );
+        assert_eq!(
+            parse_git_version("git version 2.45.1.windows.1"),
+            Some((2, 45))
+        );
+        assert_eq!(parse_git_version("not git"), None);
+    }
+
+    #[test]
+    fn test_min_git_version_comparison() {
+        assert!((2, 17) >= MIN_GIT_VERSION);
+        assert!((3, 0) >= MIN_GIT_VERSION);
+        assert!((2, 16) < MIN_GIT_VERSION);
+    }
+
+    #[test]
+    fn test_find_program_searches_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("fake-agent");
+        std::fs::write(&program, "#!/bin/sh\n").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let path_var = std::env::join_paths([dir.path()]).unwrap();
+        assert_eq!(
+            find_program("fake-agent", Some(&path_var)),
+            Some(program.clone())
+        );
+        assert_eq!(find_program("missing-agent", Some(&path_var)), None);
+        assert_eq!(find_program(program.to_str().unwrap(), None), Some(program));
+    }
+}
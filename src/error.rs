@@ -0,0 +1,94 @@
+use std::io;
+use std::path::PathBuf;
+
+/// Failures from wt's library modules. Callers embedding `wt` can match on
+/// the variant; the binary just prints the message through anyhow.
+#[derive(Debug, thiserror::Error)]
+pub enum WtError {
+    #[error("Not a git repository: {}", .0.display())]
+    NotAGitRepo(PathBuf),
+
+    #[error("Cannot create nested worktrees: already inside worktree dir {}", .0.display())]
+    NestedWorktree(PathBuf),
+
+    #[error("'{0}' is not a valid git branch name")]
+    InvalidBranchName(String),
+
+    #[error("Worktree path already exists: {}", .0.display())]
+    WorktreeExists(PathBuf),
+
+    /// git refuses to check a branch out in two worktrees at once.
+    #[error(
+        "Branch '{branch}' is already checked out at {}; {}",
+        .path.display(),
+        checked_out_hint(.branch, *.main_checkout)
+    )]
+    BranchCheckedOut {
+        branch: String,
+        path: PathBuf,
+        main_checkout: bool,
+    },
+
+    #[error("Worktree '{0}' not found")]
+    WorktreeNotFound(String),
+
+    #[error("Remote branch '{0}' not found (try `git fetch`)")]
+    RemoteBranchNotFound(String),
+
+    /// Several remotes have a branch with this name and none was picked.
+    #[error("Multiple remote branches match '{0}' and none was selected")]
+    RemoteBranchNotSelected(String),
+
+    /// A git command ran but exited non-zero.
+    #[error("git {args} failed: {stderr}")]
+    Git { args: String, stderr: String },
+
+    /// A tmux command ran but exited non-zero.
+    #[error("tmux {args} failed: {stderr}")]
+    Tmux { args: String, stderr: String },
+
+    /// A configured hook exited non-zero.
+    #[error("{hook} hook for '{name}' failed ({status})")]
+    HookFailed {
+        hook: String,
+        name: String,
+        status: String,
+    },
+
+    #[error("'{0}' is not a tmux layout name or a percentage like 70%")]
+    InvalidLayout(String),
+
+    #[error("Failed to attach to tmux session '{0}'")]
+    Attach(String),
+
+    /// A command succeeded but printed something wt could not parse.
+    #[error("Unexpected output from {command}: {output:?}")]
+    UnexpectedOutput { command: String, output: String },
+
+    /// Spawning a command or touching a file failed.
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+impl WtError {
+    pub(crate) fn io(context: impl Into<String>, source: io::Error) -> Self {
+        Self::Io {
+            context: context.into(),
+            source,
+        }
+    }
+}
+
+fn checked_out_hint(branch: &str, main_checkout: bool) -> String {
+    if main_checkout {
+        "switch that checkout to another branch first".to_string()
+    } else {
+        format!("run `wt use {}` to enter it", branch)
+    }
+}
+
+pub type Result<T, E = WtError> = std::result::Result<T, E>;
@@ -0,0 +1,324 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::command::CommandExt;
+use crate::error::{Result, WtError};
+
+/// Typed wrappers around the git invocations wt makes against one
+/// repository or worktree. Every failure is reported the same way:
+/// `git <args> failed: <stderr>`.
+#[derive(Debug, Clone)]
+pub struct Git {
+    repo_path: PathBuf,
+    default_branch: OnceLock<String>,
+}
+
+impl Git {
+    pub fn new(repo_path: impl Into<PathBuf>) -> Self {
+        Self {
+            repo_path: repo_path.into(),
+            default_branch: OnceLock::new(),
+        }
+    }
+
+    /// Run `git <args>` in the repository and return its trimmed stdout.
+    pub fn run<I, S>(&self, args: I) -> Result<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let args: Vec<S> = args.into_iter().collect();
+        let display = args
+            .iter()
+            .map(|arg| arg.as_ref().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let output = Command::new("git")
+            .args(&args)
+            .current_dir(&self.repo_path)
+            .run()
+            .map_err(|e| WtError::io(format!("Failed to execute git {}", display), e))?;
+
+        if !output.status.success() {
+            return Err(WtError::Git {
+                args: display,
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Run `git <args>` for its exit status alone, e.g. `show-ref --verify`.
+    pub fn succeeds<I, S>(&self, args: I) -> bool
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        Command::new("git")
+            .args(args)
+            .current_dir(&self.repo_path)
+            .run()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    pub fn rev_parse(&self, args: &[&str]) -> Result<String> {
+        self.run(std::iter::once("rev-parse").chain(args.iter().copied()))
+    }
+
+    /// Top-level directory of the checkout containing `repo_path`.
+    pub fn show_toplevel(&self) -> Result<PathBuf> {
+        self.rev_parse(&["--show-toplevel"]).map(PathBuf::from)
+    }
+
+    /// Main checkout of the repository, even when `repo_path` is in a
+    /// linked worktree. A shared git dir named `.git` sits in the main
+    /// checkout; any other (a submodule's `.git/modules/<name>`, a custom
+    /// `GIT_DIR`) names its checkout via `core.worktree`. Falls back to the
+    /// current checkout, then to `repo_path`.
+    pub fn main_checkout(&self) -> PathBuf {
+        let main = self
+            .rev_parse(&["--path-format=absolute", "--git-common-dir"])
+            .ok()
+            .map(PathBuf::from)
+            .and_then(|common_dir| {
+                if common_dir.file_name().is_some_and(|name| name == ".git") {
+                    return common_dir.parent().map(Path::to_path_buf);
+                }
+                self.run([
+                    OsStr::new("--git-dir"),
+                    common_dir.as_os_str(),
+                    OsStr::new("rev-parse"),
+                    OsStr::new("--show-toplevel"),
+                ])
+                .ok()
+                .map(PathBuf::from)
+            })
+            .or_else(|| self.show_toplevel().ok())
+            .unwrap_or_else(|| self.repo_path.clone());
+        main.canonicalize().unwrap_or(main)
+    }
+
+    /// Branch checked out at `repo_path`, or `HEAD` when detached.
+    pub fn current_branch(&self) -> Result<String> {
+        self.rev_parse(&["--abbrev-ref", "HEAD"])
+    }
+
+    /// The repository's default branch, detected on first use and cached
+    /// for the life of this `Git`. In order: `wt.defaultBranch` from git
+    /// config, origin's HEAD, `init.defaultBranch` if that branch exists,
+    /// `main` or `master` if either exists, else `main`.
+    pub fn default_branch(&self) -> &str {
+        self.default_branch
+            .get_or_init(|| self.detect_default_branch())
+    }
+
+    fn detect_default_branch(&self) -> String {
+        if let Some(branch) = self.config_value("wt.defaultBranch") {
+            return branch;
+        }
+
+        if let Ok(refname) = self.run(["symbolic-ref", "refs/remotes/origin/HEAD"]) {
+            if let Some(branch) = refname.strip_prefix("refs/remotes/origin/") {
+                return branch.to_string();
+            }
+        }
+
+        let init_default = self.config_value("init.defaultBranch");
+        let branch = init_default
+            .as_deref()
+            .into_iter()
+            .chain(["main", "master"])
+            .find(|branch| self.branch_exists(branch))
+            .unwrap_or("main");
+        branch.to_string()
+    }
+
+    fn config_value(&self, key: &str) -> Option<String> {
+        self.run(["config", "--get", key])
+            .ok()
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Remote local `branch` tracks, or `None` when it tracks nothing or
+    /// another local branch.
+    pub fn branch_remote(&self, branch: &str) -> Option<String> {
+        self.config_value(&format!("branch.{}.remote", branch))
+            .filter(|remote| remote != ".")
+    }
+
+    /// Upstream of local `branch` (e.g. `origin/main`), if it has one.
+    pub fn upstream(&self, branch: &str) -> Option<String> {
+        self.rev_parse(&["--abbrev-ref", &format!("{}@{{upstream}}", branch)])
+            .ok()
+    }
+
+    fn branch_exists(&self, branch: &str) -> bool {
+        self.succeeds([
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", branch),
+        ])
+    }
+
+    /// Commits `(ahead, behind)` of HEAD relative to `other`.
+    pub fn ahead_behind(&self, other: &str) -> Result<(u32, u32)> {
+        let output = self.run([
+            "rev-list",
+            "--left-right",
+            "--count",
+            &format!("HEAD...{}", other),
+        ])?;
+
+        let mut counts = output.split_whitespace().map(str::parse::<u32>);
+        if let (Some(Ok(ahead)), Some(Ok(behind))) = (counts.next(), counts.next()) {
+            return Ok((ahead, behind));
+        }
+        Err(WtError::UnexpectedOutput {
+            command: "git rev-list --left-right --count".to_string(),
+            output,
+        })
+    }
+
+    /// Local branch names, or remote-tracking ones such as `origin/x` when
+    /// `remote` is set. Symbolic refs like `origin/HEAD` are skipped.
+    pub fn branches(&self, remote: bool) -> Result<Vec<String>> {
+        let namespace = if remote { "refs/remotes" } else { "refs/heads" };
+        self.run([
+            "for-each-ref",
+            "--format=%(refname:short)%09%(symref)",
+            namespace,
+        ])
+        .map(|output| parse_branch_list(&output))
+    }
+
+    /// `git worktree list --porcelain` output.
+    pub fn worktree_list(&self) -> Result<String> {
+        self.run(["worktree", "list", "--porcelain"])
+    }
+
+    /// Whether the checkout has uncommitted changes, untracked files included.
+    pub fn is_dirty(&self) -> Result<bool> {
+        self.run(["status", "--porcelain"]).map(|s| !s.is_empty())
+    }
+}
+
+/// Parse `<name>\t<symref>` lines, keeping only real branches.
+fn parse_branch_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, symref) = line.split_once('\t').unwrap_or((line, ""));
+            (!name.is_empty() && symref.is_empty()).then(|| name.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup_git_repo(branch: &str) -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let git = Git::new(temp_dir.path());
+
+        git.run(["init", "-b", branch]).unwrap();
+        git.run(["config", "user.email", "test@example.com"])
+            .unwrap();
+        git.run(["config", "user.name", "Test User"]).unwrap();
+        fs::write(temp_dir.path().join("README.md"), "# Test Repo\n").unwrap();
+        git.run(["add", "."]).unwrap();
+        git.run(["commit", "-m", "Initial commit"]).unwrap();
+
+        temp_dir
+    }
+
+    #[test]
+    fn test_current_branch_and_toplevel() {
+        let repo = setup_git_repo("main");
+        let subdir = repo.path().join("src");
+        fs::create_dir(&subdir).unwrap();
+
+        let git = Git::new(&subdir);
+        assert_eq!(git.current_branch().unwrap(), "main");
+        assert_eq!(
+            git.show_toplevel().unwrap().canonicalize().unwrap(),
+            repo.path().canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_default_branch_falls_back_to_master() {
+        let repo = setup_git_repo("master");
+        assert_eq!(Git::new(repo.path()).default_branch(), "master");
+
+        let repo = setup_git_repo("trunk");
+        assert_eq!(Git::new(repo.path()).default_branch(), "main");
+    }
+
+    #[test]
+    fn test_default_branch_from_git_config() {
+        let repo = setup_git_repo("develop");
+        let git = Git::new(repo.path());
+        git.run(["config", "init.defaultBranch", "develop"])
+            .unwrap();
+        assert_eq!(Git::new(repo.path()).default_branch(), "develop");
+
+        // wt.defaultBranch wins, and the first answer is kept afterwards
+        git.run(["branch", "release"]).unwrap();
+        git.run(["config", "wt.defaultBranch", "release"]).unwrap();
+        assert_eq!(git.default_branch(), "release");
+        git.run(["config", "wt.defaultBranch", "develop"]).unwrap();
+        assert_eq!(git.default_branch(), "release");
+    }
+
+    #[test]
+    fn test_parse_branch_list_skips_symbolic_refs() {
+        let output = "origin/HEAD\trefs/remotes/origin/main\norigin/main\t\norigin/fix\t";
+        assert_eq!(parse_branch_list(output), vec!["origin/main", "origin/fix"]);
+        assert!(parse_branch_list("").is_empty());
+    }
+
+    #[test]
+    fn test_branches_lists_local_branches() {
+        let repo = setup_git_repo("main");
+        let git = Git::new(repo.path());
+        git.run(["branch", "feature/x"]).unwrap();
+
+        assert_eq!(git.branches(false).unwrap(), vec!["feature/x", "main"]);
+        assert!(git.branches(true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_is_dirty() {
+        let repo = setup_git_repo("main");
+        let git = Git::new(repo.path());
+        assert!(!git.is_dirty().unwrap());
+
+        fs::write(repo.path().join("new.txt"), "hello").unwrap();
+        assert!(git.is_dirty().unwrap());
+    }
+
+    #[test]
+    fn test_run_error_includes_args_and_stderr() {
+        let repo = setup_git_repo("main");
+        let error = Git::new(repo.path())
+            .rev_parse(&["--verify", "no-such-branch"])
+            .unwrap_err()
+            .to_string();
+
+        assert!(
+            error.starts_with("git rev-parse --verify no-such-branch failed: "),
+            "{}",
+            error
+        );
+        assert!(error.contains("Needed a single revision"), "{}", error);
+    }
+}
@@ -0,0 +1,164 @@
+//! Executable lifecycle hooks discovered in a repo's `.wt/hooks/`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::command::CommandExt;
+use crate::error::{Result, WtError};
+
+/// Directory, relative to the repo root, that holds hook scripts.
+pub const HOOKS_DIR: &str = ".wt/hooks";
+
+/// Run in a new worktree right after it is created.
+pub const POST_CREATE: &str = "post-create";
+
+/// Run in a worktree just before it is removed.
+pub const PRE_REMOVE: &str = "pre-remove";
+
+/// The script for hook `name` under `repo_root`, if it exists and is
+/// executable.
+pub fn find_hook(repo_root: &Path, name: &str) -> Option<PathBuf> {
+    let path = repo_root.join(HOOKS_DIR).join(name);
+    is_executable(&path).then_some(path)
+}
+
+/// Run hook `hook` from `repo_root` inside the worktree at `path` (see
+/// `run_in_worktree`). Returns `false` without doing anything when there
+/// is no such hook.
+pub fn run_hook(repo_root: &Path, hook: &str, name: &str, path: &Path) -> Result<bool> {
+    let Some(script) = find_hook(repo_root, hook) else {
+        return Ok(false);
+    };
+    run_in_worktree(Command::new(&script), hook, name, path)?;
+    Ok(true)
+}
+
+/// Run a hook given as a shell command in config, e.g. `hooks.pre_remove`,
+/// the same way as a script hook.
+pub fn run_command_hook(command: &str, hook: &str, name: &str, path: &Path) -> Result<()> {
+    let mut sh = Command::new("sh");
+    sh.args(["-c", command]);
+    run_in_worktree(sh, hook, name, path)
+}
+
+/// Run `command` inside the worktree at `path` with `WT_NAME` and
+/// `WT_PATH` set. Its output goes to stderr so it can't mix with paths wt
+/// prints on stdout; a non-zero exit is `WtError::HookFailed`.
+fn run_in_worktree(mut command: Command, hook: &str, name: &str, path: &Path) -> Result<()> {
+    let status = command
+        .current_dir(path)
+        .env("WT_NAME", name)
+        .env("WT_PATH", path)
+        .stdout(std::io::stderr())
+        .run_status()
+        .map_err(|e| WtError::io(format!("Failed to run {} hook", hook), e))?;
+    if !status.success() {
+        return Err(WtError::HookFailed {
+            hook: hook.to_string(),
+            name: name.to_string(),
+            status: status.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Whether `path` is an executable file. `pub` because `wt doctor`,
+/// in the binary crate, uses it for its PATH lookup.
+pub fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_hook(repo: &Path, name: &str, body: &str, mode: u32) {
+        let dir = repo.join(HOOKS_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_missing_hook_is_a_no_op() {
+        let repo = TempDir::new().unwrap();
+        assert!(!run_hook(repo.path(), POST_CREATE, "x", repo.path()).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_executable_hook_is_ignored() {
+        let repo = TempDir::new().unwrap();
+        write_hook(repo.path(), POST_CREATE, "exit 1", 0o644);
+
+        assert_eq!(find_hook(repo.path(), POST_CREATE), None);
+        assert!(!run_hook(repo.path(), POST_CREATE, "x", repo.path()).unwrap());
+    }
+
+    #[test]
+    fn test_hook_runs_in_worktree_with_env() {
+        let repo = TempDir::new().unwrap();
+        let worktree = TempDir::new().unwrap();
+        write_hook(
+            repo.path(),
+            POST_CREATE,
+            "echo \"$WT_NAME $WT_PATH\" > marker",
+            0o755,
+        );
+
+        assert!(run_hook(repo.path(), POST_CREATE, "feature/x", worktree.path()).unwrap());
+
+        let marker = fs::read_to_string(worktree.path().join("marker")).unwrap();
+        assert_eq!(
+            marker.trim(),
+            format!("feature/x {}", worktree.path().display())
+        );
+    }
+
+    #[test]
+    fn test_failing_hook_reports_hook_and_worktree() {
+        let repo = TempDir::new().unwrap();
+        write_hook(repo.path(), PRE_REMOVE, "exit 2", 0o755);
+
+        let error = run_hook(repo.path(), PRE_REMOVE, "feature/x", repo.path()).unwrap_err();
+        assert!(matches!(
+            &error,
+            WtError::HookFailed { hook, name, .. } if hook == PRE_REMOVE && name == "feature/x"
+        ));
+    }
+
+    #[test]
+    fn test_command_hook_runs_like_a_script_hook() {
+        let worktree = TempDir::new().unwrap();
+
+        run_command_hook(
+            "echo \"$WT_NAME\" > marker",
+            "pre_remove",
+            "x",
+            worktree.path(),
+        )
+        .unwrap();
+        let marker = fs::read_to_string(worktree.path().join("marker")).unwrap();
+        assert_eq!(marker.trim(), "x");
+
+        let error = run_command_hook("exit 3", "pre_remove", "x", worktree.path()).unwrap_err();
+        assert!(matches!(error, WtError::HookFailed { hook, .. } if hook == "pre_remove"));
+    }
+}
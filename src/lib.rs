@@ -1,4 +1,8 @@
+pub mod command;
 pub mod config;
+pub mod error;
+pub mod git;
+pub mod hooks;
 pub mod session;
 pub mod shell;
 pub mod tmux_manager;
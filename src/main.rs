@@ -1,19 +1,29 @@
+mod doctor;
 mod session_cmd;
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use dialoguer::Select;
+use dialoguer::{Confirm, FuzzySelect, Select};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use doctor::run_doctor;
 use session_cmd::{run_session, SessionAction};
-use wt::config::SessionMode;
+use wt::command::{set_verbose, CommandExt};
+use wt::config::{Config, SessionMode};
+use wt::git::Git;
+use wt::hooks;
 use wt::shell::spawn_wt_shell;
+use wt::tmux_manager::{TmuxManager, AGENT_LOG_FILE};
 use wt::worktree_manager::{
-    check_not_in_worktree, ensure_worktrees_in_gitignore, get_current_worktree_name,
+    check_not_in_worktree, ensure_git_excluded, ensure_worktrees_in_gitignore,
+    get_current_worktree_name, glob_match, issue_branch_name, worktree_path_for, WorktreeInfo,
     WorktreeManager,
 };
 
+const DEFAULT_WORKTREE_DIR: &str = ".worktrees";
+
 #[derive(Parser)]
 #[command(
     name = "wt",
@@ -21,24 +31,70 @@ use wt::worktree_manager::{
     about = "Parallel workspaces for agent sandboxes"
 )]
 struct Cli {
-    /// Worktree directory (relative to repo root)
-    #[arg(short = 'd', long, global = true, default_value = ".worktrees")]
-    dir: PathBuf,
+    /// Worktree directory (relative to repo root) [default: worktree_dir
+    /// from config, else .worktrees]
+    #[arg(short = 'd', long, global = true)]
+    dir: Option<PathBuf>,
+
+    /// Operate on the repository at this path instead of the current directory
+    #[arg(long, value_name = "PATH", global = true)]
+    repo: Option<PathBuf>,
+
+    /// Log every git/tmux command wt runs, with its exit status
+    #[arg(short, long, global = true)]
+    verbose: bool,
 
     #[command(subcommand)]
     command: Commands,
 }
 
-struct RepoConfig {
+/// Facts about the current repo, looked up once per invocation and passed
+/// to every command instead of re-running git for them.
+struct RepoContext {
+    /// The main checkout, even when wt runs inside a linked worktree
     root: PathBuf,
+    /// The checkout wt runs in: `root`, or the worktree it is inside
+    checkout: PathBuf,
+    git: Git,
     worktree_dir: PathBuf,
+    config: Config,
 }
 
-impl RepoConfig {
-    fn new(dir: &Path) -> Result<Self> {
-        let root = get_repo_root()?;
+impl RepoContext {
+    /// Find the repo from `--repo` if given, else the current directory.
+    /// Resolve the worktree dir against the main checkout with precedence:
+    /// `-d` flag > `worktree_dir` in config > `.worktrees`.
+    fn new(repo: Option<&Path>, dir: Option<&Path>) -> Result<Self> {
+        let checkout = match repo {
+            Some(repo) => Git::new(repo)
+                .show_toplevel()
+                .with_context(|| format!("Not a git repository: {}", repo.display()))?,
+            None => Git::new(".")
+                .show_toplevel()
+                .context("Not a git repository")?,
+        };
+        let root = Git::new(&checkout).main_checkout();
+        let config = Config::load_for_repo(&root);
+        let dir = match dir {
+            Some(dir) => dir.to_path_buf(),
+            None => config
+                .worktree_dir
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_WORKTREE_DIR)),
+        };
         let worktree_dir = root.join(dir);
-        Ok(Self { root, worktree_dir })
+        Ok(Self {
+            git: Git::new(&root),
+            root,
+            checkout,
+            worktree_dir,
+            config,
+        })
+    }
+
+    /// The repo's default branch, detected on first use.
+    fn root_branch(&self) -> &str {
+        self.git.default_branch()
     }
 }
 
@@ -48,27 +104,121 @@ enum Commands {
     New {
         /// Name for the workspace (defaults to current branch, fails on root branch)
         name: Option<String>,
-        /// Base branch to create from
-        #[arg(short, default_value = "main")]
-        b: String,
+        /// Base branch to create from (defaults to the repo's default branch)
+        #[arg(short)]
+        b: Option<String>,
         /// Print path instead of entering shell (for scripts/agents)
         #[arg(long)]
         print_path: bool,
+        /// Name the workspace after a GitHub issue (e.g. 123-fix-auth-bug)
+        #[arg(long, value_name = "NUMBER", conflicts_with = "name")]
+        issue: Option<u64>,
+        /// Create a new branch tracking this remote branch (e.g. origin/feature);
+        /// the name defaults to the part after the remote
+        #[arg(long, value_name = "REMOTE/BRANCH", conflicts_with_all = ["b", "issue"])]
+        track: Option<String>,
+        /// Open the workspace in a new tmux window split into this many panes
+        /// (1-3) instead of a subshell; requires running inside tmux
+        #[arg(long, value_name = "N", conflicts_with = "print_path")]
+        panes: Option<u8>,
+        /// Apply this stash (e.g. stash@{1}) in the new workspace; the stash
+        /// itself is kept
+        #[arg(long, value_name = "STASH")]
+        from_stash: Option<String>,
+        /// Never move work off the current branch, even when the name
+        /// matches it (fails, as git can't check a branch out twice)
+        #[arg(long, conflicts_with = "track")]
+        no_migrate: bool,
     },
     /// Enter an existing workspace subshell
     Use {
         /// Name of the workspace (optional if already in worktree)
         name: Option<String>,
+        /// Print path instead of entering shell (for scripts/agents)
+        #[arg(long)]
+        print_path: bool,
+    },
+    /// Print the path a workspace would be created at, without creating it
+    Path {
+        /// Name of the workspace
+        name: String,
+    },
+    /// Open a workspace in the configured editor
+    Open {
+        /// Name of the workspace (optional if already in worktree)
+        name: Option<String>,
+    },
+    /// Bring a workspace up to date with its base branch, or the base's
+    /// upstream when it has one (fast-forward only)
+    Sync {
+        /// Name of the workspace (optional if already in worktree)
+        name: Option<String>,
+        /// Base branch to sync from [default: the repo's default branch]
+        #[arg(short)]
+        b: Option<String>,
+        /// Rebase onto the base branch when it can't fast-forward
+        #[arg(long)]
+        rebase: bool,
+    },
+    /// Show a workspace's changes since it branched off its base
+    Diff {
+        /// Name of the workspace (optional if already in worktree)
+        name: Option<String>,
+        /// Base branch to diff against [default: the repo's default branch]
+        #[arg(short, long)]
+        base: Option<String>,
+        /// Show a diffstat instead of the full diff
+        #[arg(long)]
+        stat: bool,
     },
     /// List all workspaces (interactive picker)
-    Ls,
+    Ls {
+        /// Print one workspace name per line instead of opening the picker
+        #[arg(long)]
+        plain: bool,
+        /// With --plain, mark the current workspace with " *"
+        #[arg(long, requires = "plain")]
+        marker: bool,
+        /// With --plain, add tab-separated dirty/clean and ahead/behind
+        /// columns (runs git status in every workspace)
+        #[arg(long, requires = "plain")]
+        status: bool,
+    },
+    /// List branches that don't have a workspace yet
+    ListBranches {
+        /// Also list remote branches with no local branch or workspace
+        #[arg(long)]
+        remote: bool,
+    },
     /// Remove a workspace
     Rm {
         /// Name of the workspace to remove (interactive if omitted)
+        #[arg(conflicts_with = "all")]
         name: Option<String>,
+        /// Remove all workspaces (or those matching --match)
+        #[arg(long)]
+        all: bool,
+        /// Only remove workspaces whose name matches this glob (with --all)
+        #[arg(long = "match", value_name = "GLOB", requires = "all")]
+        pattern: Option<String>,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+        /// Remove even if the branch has commits not on the base branch
+        #[arg(short, long, conflicts_with = "all")]
+        force: bool,
     },
     /// Print current worktree name (or "main" if in main worktree)
-    Which,
+    Which {
+        /// Print the worktree's absolute path instead (repo root outside a worktree)
+        #[arg(long, conflicts_with = "branch")]
+        path: bool,
+        /// Print the checked-out branch instead
+        #[arg(long)]
+        branch: bool,
+    },
+    /// Check the environment for common problems
+    Doctor,
     /// Manage tmux session with multiple worktree windows
     Session {
         /// Override session layout mode for this invocation
@@ -79,85 +229,132 @@ enum Commands {
     },
 }
 
-fn get_repo_root() -> Result<PathBuf> {
-    let output = Command::new("git")
-        .args(["rev-parse", "--show-toplevel"])
-        .output()
-        .context("Failed to execute git rev-parse")?;
-
-    if !output.status.success() {
-        anyhow::bail!("Not a git repository");
-    }
-
-    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    Ok(PathBuf::from(path))
-}
-
-fn get_current_branch() -> Result<String> {
-    let output = Command::new("git")
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-        .output()
-        .context("Failed to get current branch")?;
-
-    if !output.status.success() {
-        anyhow::bail!("Failed to determine current branch");
-    }
-
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-}
-
-fn get_root_branch() -> String {
-    // Try to get the default branch from remote
-    if let Ok(output) = Command::new("git")
-        .args(["symbolic-ref", "refs/remotes/origin/HEAD"])
-        .output()
-    {
-        if output.status.success() {
-            let refname = String::from_utf8_lossy(&output.stdout);
-            if let Some(branch) = refname.trim().strip_prefix("refs/remotes/origin/") {
-                return branch.to_string();
-            }
-        }
-    }
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    set_verbose(cli.verbose);
 
-    // Fall back to checking if main or master exists
-    for branch in ["main", "master"] {
-        if Command::new("git")
-            .args(["rev-parse", "--verify", branch])
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
-        {
-            return branch.to_string();
-        }
+    // Doctor must run outside a repo too, so it can report that problem
+    if let Commands::Doctor = cli.command {
+        return run_doctor(cli.repo.as_deref());
     }
 
-    "main".to_string()
-}
-
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let config = RepoConfig::new(&cli.dir)?;
+    let repo = RepoContext::new(cli.repo.as_deref(), cli.dir.as_deref())?;
 
     match cli.command {
         Commands::New {
             name,
             b,
             print_path,
-        } => cmd_new(&config, name, &b, print_path),
-        Commands::Use { name } => cmd_use(&config, name),
-        Commands::Ls => cmd_ls(&config),
-        Commands::Rm { name } => cmd_rm(&config, name),
-        Commands::Which => cmd_which(&config.root),
-        Commands::Session { mode, action } => run_session(&config, mode, action),
+            issue,
+            track,
+            panes,
+            from_stash,
+            no_migrate,
+        } => {
+            let name = name
+                .or_else(|| {
+                    issue.map(|number| {
+                        issue_branch_name(number, fetch_issue_title(&repo.root, number).as_deref())
+                    })
+                })
+                .or_else(|| {
+                    let (_, branch) = track.as_deref()?.split_once('/')?;
+                    Some(branch.to_string())
+                });
+            let options = NewOptions {
+                base: b.as_deref().unwrap_or_else(|| repo.root_branch()),
+                track: track.as_deref(),
+                print_path,
+                panes,
+                from_stash: from_stash.as_deref(),
+                no_migrate,
+            };
+            cmd_new(&repo, name, &options)
+        }
+        Commands::Use { name, print_path } => cmd_use(&repo, name, print_path),
+        Commands::Open { name } => cmd_open(&repo, name),
+        Commands::Sync { name, b, rebase } => cmd_sync(&repo, name, b.as_deref(), rebase),
+        Commands::Diff { name, base, stat } => cmd_diff(&repo, name, base.as_deref(), stat),
+        Commands::Path { name } => {
+            println!("{}", worktree_path_for(&repo.worktree_dir, &name).display());
+            Ok(())
+        }
+        Commands::Ls {
+            plain,
+            marker,
+            status,
+        } => {
+            if plain {
+                let manager = WorktreeManager::new(repo.root.clone())?;
+                if status {
+                    let worktrees = manager.list_managed_with_status(&repo.worktree_dir)?;
+                    print_worktree_names(&worktrees, marker, true);
+                } else {
+                    print_worktree_names(&manager.list_managed(&repo.worktree_dir)?, marker, false);
+                }
+                Ok(())
+            } else {
+                cmd_ls(&repo)
+            }
+        }
+        Commands::Rm {
+            name,
+            all,
+            pattern,
+            yes,
+            force,
+        } => {
+            if all {
+                cmd_rm_all(&repo, pattern.as_deref(), yes)
+            } else {
+                cmd_rm(&repo, name, force)
+            }
+        }
+        Commands::ListBranches { remote } => cmd_list_branches(&repo, remote),
+        Commands::Which { path, branch } => cmd_which(&repo, path, branch),
+        Commands::Session { mode, action } => run_session(&repo, mode, action),
+        Commands::Doctor => unreachable!("handled before repo detection"),
     }
 }
 
-fn cmd_new(config: &RepoConfig, name: Option<String>, base: &str, print_path: bool) -> Result<()> {
-    check_not_in_worktree(&config.root)?;
+struct NewOptions<'a> {
+    base: &'a str,
+    track: Option<&'a str>,
+    print_path: bool,
+    panes: Option<u8>,
+    from_stash: Option<&'a str>,
+    no_migrate: bool,
+}
+
+fn cmd_new(repo: &RepoContext, name: Option<String>, options: &NewOptions<'_>) -> Result<()> {
+    let NewOptions {
+        base,
+        track,
+        print_path,
+        panes,
+        from_stash,
+        no_migrate,
+    } = *options;
+    check_not_in_worktree(&repo.checkout, &repo.worktree_dir)?;
+    if panes.is_some() && !TmuxManager::is_inside_tmux() {
+        anyhow::bail!("--panes needs tmux; run wt new inside a tmux session or drop --panes");
+    }
+    // Resolve now: a migration stash below would shift stash@{N} refs
+    let stash = from_stash
+        .map(|stash| {
+            repo.git
+                .run([
+                    "rev-parse",
+                    "--verify",
+                    "--quiet",
+                    &format!("{}^{{commit}}", stash),
+                ])
+                .map_err(|_| anyhow::anyhow!("No stash '{}'; see git stash list", stash))
+        })
+        .transpose()?;
 
-    let current_branch = get_current_branch()?;
-    let root_branch = get_root_branch();
+    let current_branch = Git::new(&repo.checkout).current_branch()?;
+    let root_branch = repo.root_branch();
 
     let name = match name {
         Some(n) => n,
@@ -172,68 +369,149 @@ fn cmd_new(config: &RepoConfig, name: Option<String>, base: &str, print_path: bo
         }
     };
 
+    if no_migrate && name == current_branch {
+        anyhow::bail!(
+            "'{}' is checked out in {}, and git can't check a branch out twice.\n\
+             Drop --no-migrate to move your work into a worktree, or switch {} \
+             to another branch first.",
+            name,
+            repo.checkout.display(),
+            repo.checkout.display()
+        );
+    }
+
     // If creating worktree for currently checked out branch, migrate the work
-    let migrating = name == current_branch && current_branch != root_branch;
+    let migrating = track.is_none() && name == current_branch && current_branch != root_branch;
     let had_changes = if migrating {
-        migrate_from_current_branch(&config.root, &root_branch)?
+        migrate_from_current_branch(&repo.checkout, root_branch)?
     } else {
         false
     };
 
-    let manager = WorktreeManager::new(config.root.clone())?;
-    ensure_worktrees_in_gitignore(&config.root, &config.worktree_dir)?;
-    std::fs::create_dir_all(&config.worktree_dir)?;
-    let path = manager.create_worktree(&name, base, &config.worktree_dir, |remotes| {
-        choose_remote_branch(&name, remotes)
-    })?;
+    let manager = WorktreeManager::new(repo.root.clone())?;
+    ensure_worktrees_in_gitignore(&repo.root, &repo.worktree_dir)?;
+    std::fs::create_dir_all(&repo.worktree_dir)?;
+    let path = match track {
+        Some(remote_ref) => {
+            manager.create_tracking_worktree(&name, remote_ref, &repo.worktree_dir)?
+        }
+        None => manager.create_worktree(&name, base, &repo.worktree_dir, |remotes| {
+            choose_remote_branch(&name, remotes)
+        })?,
+    };
 
     // Pop stash in the new worktree if we migrated changes
     if had_changes {
         let output = Command::new("git")
             .args(["stash", "pop"])
             .current_dir(&path)
-            .output()
+            .run()
             .context("Failed to pop stash")?;
         if !output.status.success() {
-            eprintln!(
-                "Warning: Failed to restore changes: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
+            report_failed_stash_pop(&path, &String::from_utf8_lossy(&output.stderr));
+        }
+    }
+    if let (Some(stash), Some(from_stash)) = (&stash, from_stash) {
+        let output = Command::new("git")
+            .args(["stash", "apply", stash])
+            .current_dir(&path)
+            .run()
+            .context("Failed to apply stash")?;
+        if output.status.success() {
+            eprintln!("Applied {} to {}", from_stash, name);
+        } else {
+            report_failed_stash_pop(&path, &String::from_utf8_lossy(&output.stderr));
         }
     }
+    run_post_create_hook(&repo.root, &name, &path);
 
     if print_path {
         println!("{}", path.display());
+    } else if let Some(panes) = panes {
+        open_layout_window(repo, &name, &path, panes)?;
     } else {
-        spawn_wt_shell(&path, &name, &name)?;
+        spawn_wt_shell(&path, &name, &name, &repo.config.shell)?;
     }
     Ok(())
 }
 
-fn choose_remote_branch(name: &str, remotes: &[String]) -> Result<String> {
-    if remotes.is_empty() {
-        anyhow::bail!("No remote branches match '{}'.", name);
+/// Open `path` in a new window of the tmux session we're running in, laid
+/// out like a `wt session add` window.
+fn open_layout_window(repo: &RepoContext, name: &str, path: &Path, panes: u8) -> Result<()> {
+    let tmux = TmuxManager::new(&TmuxManager::current_session()?);
+    let panes = repo.config.effective_panes(Some(panes));
+    if repo.config.session.log_agent_output {
+        ensure_git_excluded(&repo.root, AGENT_LOG_FILE)?;
     }
+    tmux.create_window(name, path)?;
+    tmux.setup_worktree_layout(
+        name,
+        path,
+        panes,
+        repo.config.session.layout.as_deref(),
+        &repo.config.session,
+    )?;
+    eprintln!("Opened window: {} ({} panes)", name, panes);
+    Ok(())
+}
+
+/// Look up an issue title with the `gh` CLI. `None` if gh is missing,
+/// unauthenticated, or the issue doesn't exist.
+fn fetch_issue_title(repo_path: &Path, number: u64) -> Option<String> {
+    let output = Command::new("gh")
+        .args([
+            "issue",
+            "view",
+            &number.to_string(),
+            "--json",
+            "title",
+            "-q",
+            ".title",
+        ])
+        .current_dir(repo_path)
+        .run()
+        .ok()?;
 
-    if remotes.len() == 1 {
-        return Ok(remotes[0].clone());
+    if !output.status.success() {
+        eprintln!("Could not fetch issue #{}; using issue-{}", number, number);
+        return None;
     }
 
+    let title = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!title.is_empty()).then_some(title)
+}
+
+/// Ask which of several remote branches named `name` to track. `None` if
+/// the prompt is cancelled or there is no terminal to ask on.
+fn choose_remote_branch(name: &str, remotes: &[String]) -> Option<String> {
     let selection = Select::new()
         .with_prompt(format!("Select remote branch for '{}'", name))
         .items(remotes)
         .default(0)
-        .interact()?;
+        .interact_opt()
+        .ok()??;
 
-    Ok(remotes[selection].clone())
+    remotes.get(selection).cloned()
 }
 
 fn migrate_from_current_branch(repo_path: &Path, root_branch: &str) -> Result<bool> {
+    // Untracked files are not stashed, so bail before touching anything if
+    // the checkout below would refuse to overwrite them
+    let blocking = untracked_files_blocking_checkout(repo_path, root_branch)?;
+    if !blocking.is_empty() {
+        anyhow::bail!(
+            "Cannot switch to {}: these untracked files would be overwritten:\n  {}\n\
+             Move or commit them and run wt new again; nothing was changed.",
+            root_branch,
+            blocking.join("\n  ")
+        );
+    }
+
     // Check for uncommitted changes
     let status = Command::new("git")
         .args(["status", "--porcelain"])
         .current_dir(repo_path)
-        .output()
+        .run()
         .context("Failed to check git status")?;
 
     let has_changes = !status.stdout.is_empty();
@@ -243,7 +521,7 @@ fn migrate_from_current_branch(repo_path: &Path, root_branch: &str) -> Result<bo
         let stash = Command::new("git")
             .args(["stash", "push", "-m", "wt: migrating to worktree"])
             .current_dir(repo_path)
-            .output()
+            .run()
             .context("Failed to stash changes")?;
         if !stash.status.success() {
             anyhow::bail!(
@@ -257,16 +535,22 @@ fn migrate_from_current_branch(repo_path: &Path, root_branch: &str) -> Result<bo
     let checkout = Command::new("git")
         .args(["checkout", root_branch])
         .current_dir(repo_path)
-        .output()
+        .run()
         .context("Failed to switch branches")?;
 
     if !checkout.status.success() {
         // Try to restore stash if checkout failed
         if has_changes {
-            let _ = Command::new("git")
+            let restored = Command::new("git")
                 .args(["stash", "pop"])
                 .current_dir(repo_path)
-                .output();
+                .run()
+                .is_ok_and(|output| output.status.success());
+            if !restored {
+                eprintln!(
+                    "wt: warning: your changes are still stashed; run `git stash pop` to restore them"
+                );
+            }
         }
         anyhow::bail!(
             "Failed to switch to {}: {}",
@@ -278,6 +562,77 @@ fn migrate_from_current_branch(repo_path: &Path, root_branch: &str) -> Result<bo
     Ok(has_changes)
 }
 
+/// Untracked files in the checkout that `root_branch` tracks, which
+/// `git checkout` would refuse to overwrite.
+fn untracked_files_blocking_checkout(repo_path: &Path, root_branch: &str) -> Result<Vec<String>> {
+    let git = Git::new(repo_path);
+    let untracked = git.run(["ls-files", "--others", "--exclude-standard"])?;
+    if untracked.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tracked = git.run(["ls-tree", "-r", "--name-only", root_branch])?;
+    let tracked: HashSet<&str> = tracked.lines().collect();
+    Ok(untracked
+        .lines()
+        .filter(|file| tracked.contains(file))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Explain a failed `git stash pop` in the new worktree. Git keeps the
+/// stash entry when the pop fails, so nothing is lost.
+fn report_failed_stash_pop(worktree: &Path, stderr: &str) {
+    let conflicts = Git::new(worktree)
+        .run(["diff", "--name-only", "--diff-filter=U"])
+        .unwrap_or_default();
+
+    if conflicts.is_empty() {
+        eprintln!(
+            "wt: warning: could not restore your changes in {}: {}",
+            worktree.display(),
+            stderr.trim()
+        );
+    } else {
+        eprintln!(
+            "wt: warning: restoring your changes in {} conflicted in:",
+            worktree.display()
+        );
+        for file in conflicts.lines() {
+            eprintln!("  {}", file);
+        }
+    }
+    eprintln!("Your changes are still in `git stash list`; drop the entry once resolved.");
+}
+
+/// Print local branches not checked out in any worktree, then with
+/// `remote` the remote branches with neither a worktree nor a local branch.
+fn cmd_list_branches(repo: &RepoContext, remote: bool) -> Result<()> {
+    let manager = WorktreeManager::new(repo.root.clone())?;
+    let checked_out: HashSet<String> = manager
+        .list_worktrees()?
+        .into_iter()
+        .map(|wt| wt.branch)
+        .collect();
+
+    let local = repo.git.branches(false)?;
+    for branch in local.iter().filter(|branch| !checked_out.contains(*branch)) {
+        println!("{}", branch);
+    }
+
+    if remote {
+        for branch in repo.git.branches(true)? {
+            let name = branch
+                .split_once('/')
+                .map_or(branch.as_str(), |(_, name)| name);
+            if !checked_out.contains(name) && !local.iter().any(|local| local == name) {
+                println!("{}", branch);
+            }
+        }
+    }
+    Ok(())
+}
+
 enum PickResult {
     Selected(String),
     ExitShell,
@@ -285,32 +640,49 @@ enum PickResult {
     Empty,
 }
 
-fn pick_worktree(config: &RepoConfig, prompt: &str) -> Result<PickResult> {
-    let manager = WorktreeManager::new(config.root.clone())?;
-    let worktrees = manager.list_worktrees()?;
+/// One name per line, with ` *` after the current worktree if `marker`.
+fn print_worktree_names(worktrees: &[WorktreeInfo], marker: bool, status: bool) {
+    let current_wt = std::env::var("WT_NAME").ok();
+    for wt in worktrees {
+        let is_current = marker && Some(&wt.task_id) == current_wt.as_ref();
+        let name = format!("{}{}", wt.task_id, if is_current { " *" } else { "" });
+        if status {
+            println!("{}\t{}", name, status_columns(wt));
+        } else {
+            println!("{}", name);
+        }
+    }
+}
+
+/// `dirty`/`clean` (`?` if git failed), then `+ahead -behind` against the
+/// upstream or `-` without one.
+fn status_columns(wt: &WorktreeInfo) -> String {
+    let dirty = match wt.dirty {
+        Some(true) => "dirty",
+        Some(false) => "clean",
+        None => "?",
+    };
+    let upstream = match wt.ahead_behind {
+        Some((ahead, behind)) => format!("+{} -{}", ahead, behind),
+        None => "-".to_string(),
+    };
+    format!("{}\t{}", dirty, upstream)
+}
+
+fn pick_worktree(repo: &RepoContext, prompt: &str) -> Result<PickResult> {
+    let manager = WorktreeManager::new(repo.root.clone())?;
+    let wt_list = manager.list_managed(&repo.worktree_dir)?;
 
     let in_wt_shell = std::env::var("WT_ACTIVE").is_ok();
     let current_wt = std::env::var("WT_NAME").ok();
 
-    let wt_list: Vec<_> = worktrees
-        .iter()
-        .filter(|wt| !wt.task_id.is_empty())
-        .collect();
-
     if wt_list.is_empty() {
         return Ok(PickResult::Empty);
     }
 
     // Non-interactive mode if not a TTY
     if !std::io::IsTerminal::is_terminal(&std::io::stderr()) {
-        for wt in &wt_list {
-            let marker = if Some(&wt.task_id) == current_wt.as_ref() {
-                " *"
-            } else {
-                ""
-            };
-            println!("{}{}", wt.task_id, marker);
-        }
+        print_worktree_names(&wt_list, true, false);
         return Ok(PickResult::Cancelled);
     }
 
@@ -333,31 +705,27 @@ fn pick_worktree(config: &RepoConfig, prompt: &str) -> Result<PickResult> {
         items.push("← cancel".to_string());
     }
 
-    let default = if let Some(ref name) = current_wt {
-        items.iter().position(|i| i.starts_with(name)).unwrap_or(0)
-    } else {
-        0
-    };
+    let default = current_wt
+        .as_ref()
+        .and_then(|name| wt_list.iter().position(|wt| &wt.task_id == name))
+        .unwrap_or(0);
 
+    // Typing filters the list; the returned index is still into `items`
     eprintln!("{}", prompt);
-    let selection = Select::new().items(&items).default(default).interact()?;
-
-    let selected = &items[selection];
-
-    if selected == "← exit shell" {
-        return Ok(PickResult::ExitShell);
-    }
+    let selection = FuzzySelect::new()
+        .items(&items)
+        .default(default)
+        .interact()?;
 
-    if selected == "← cancel" {
-        return Ok(PickResult::Cancelled);
+    match wt_list.get(selection) {
+        Some(wt) => Ok(PickResult::Selected(wt.task_id.clone())),
+        None if in_wt_shell => Ok(PickResult::ExitShell),
+        None => Ok(PickResult::Cancelled),
     }
-
-    let wt_name = selected.trim_end_matches(" *").to_string();
-    Ok(PickResult::Selected(wt_name))
 }
 
-fn cmd_ls(config: &RepoConfig) -> Result<()> {
-    match pick_worktree(config, "Select worktree:")? {
+fn cmd_ls(repo: &RepoContext) -> Result<()> {
+    match pick_worktree(repo, "Select worktree:")? {
         PickResult::Empty => {
             eprintln!("No worktrees found.");
         }
@@ -366,20 +734,25 @@ fn cmd_ls(config: &RepoConfig) -> Result<()> {
         }
         PickResult::Cancelled => {}
         PickResult::Selected(name) => {
-            let manager = WorktreeManager::new(config.root.clone())?;
+            let manager = WorktreeManager::new(repo.root.clone())?;
             let wt_info = manager
                 .get_worktree_info(&name)?
                 .ok_or_else(|| anyhow::anyhow!("Worktree not found"))?;
-            spawn_wt_shell(&wt_info.path, &wt_info.task_id, &wt_info.branch)?;
+            spawn_wt_shell(
+                &wt_info.path,
+                &wt_info.task_id,
+                &wt_info.branch,
+                &repo.config.shell,
+            )?;
         }
     }
     Ok(())
 }
 
-fn cmd_rm(config: &RepoConfig, name: Option<String>) -> Result<()> {
+fn cmd_rm(repo: &RepoContext, name: Option<String>, force: bool) -> Result<()> {
     let name = match name {
         Some(n) => n,
-        None => match pick_worktree(config, "Remove worktree:")? {
+        None => match pick_worktree(repo, "Remove worktree:")? {
             PickResult::Selected(n) => n,
             PickResult::Empty => {
                 eprintln!("No worktrees found.");
@@ -389,38 +762,330 @@ fn cmd_rm(config: &RepoConfig, name: Option<String>) -> Result<()> {
         },
     };
 
-    let manager = WorktreeManager::new(config.root.clone())?;
+    let manager = WorktreeManager::new(repo.root.clone())?;
+    if let Some(info) = manager.get_worktree_info(&name)? {
+        let managed = manager.list_managed(&repo.worktree_dir)?;
+        if !managed.iter().any(|wt| wt.task_id == name) {
+            anyhow::bail!(
+                "Worktree '{}' at {} is not in {}, so wt won't remove it.\n\
+                 Pass -d for a different worktree dir, or use 'git worktree remove'.",
+                name,
+                info.path.display(),
+                repo.worktree_dir.display()
+            );
+        }
+    }
+    if !force && !confirm_unmerged_removal(repo, &manager, &name)? {
+        return Ok(());
+    }
+    run_pre_remove_hook(repo, &manager, &name, force)?;
     manager.remove_worktree(&name)?;
     eprintln!("Removed worktree: {}", name);
     Ok(())
 }
 
-fn cmd_which(repo_path: &Path) -> Result<()> {
-    let name = get_current_worktree_name(repo_path)?;
-    println!("{}", name);
+/// Run `.wt/hooks/post-create` in a new worktree. The worktree already
+/// exists, so a failing hook is only a warning.
+fn run_post_create_hook(repo_root: &Path, name: &str, path: &Path) {
+    if let Err(error) = hooks::run_hook(repo_root, hooks::POST_CREATE, name, path) {
+        eprintln!("wt: warning: {}", error);
+    }
+}
+
+/// Run the pre-remove hooks. A failing hook stops the removal unless
+/// `force` is set.
+fn run_pre_remove_hook(
+    repo: &RepoContext,
+    manager: &WorktreeManager,
+    name: &str,
+    force: bool,
+) -> Result<()> {
+    match pre_remove_hooks(repo, manager, name) {
+        Ok(()) => Ok(()),
+        Err(error) if force => {
+            eprintln!("wt: warning: {}; removing anyway (--force)", error);
+            Ok(())
+        }
+        Err(error) => anyhow::bail!("{}. Pass --force to remove anyway.", error),
+    }
+}
+
+/// The `pre_remove` config hook, then `.wt/hooks/pre-remove`.
+fn pre_remove_hooks(
+    repo: &RepoContext,
+    manager: &WorktreeManager,
+    name: &str,
+) -> wt::error::Result<()> {
+    let Some(info) = manager.get_worktree_info(name)? else {
+        // Let remove_worktree report the missing worktree
+        return Ok(());
+    };
+    if !info.path.exists() {
+        return Ok(());
+    }
+
+    if let Some(hook) = &repo.config.hooks.pre_remove {
+        hooks::run_command_hook(hook, "pre_remove", name, &info.path)?;
+    }
+    hooks::run_hook(&repo.root, hooks::PRE_REMOVE, name, &info.path)?;
     Ok(())
 }
 
-fn cmd_use(config: &RepoConfig, name: Option<String>) -> Result<()> {
-    let manager = WorktreeManager::new(config.root.clone())?;
-    let worktrees = manager.list_worktrees()?;
+/// Warn when the worktree's branch has commits the root branch doesn't, and
+/// ask before removing it. Returns whether removal should go ahead.
+fn confirm_unmerged_removal(
+    repo: &RepoContext,
+    manager: &WorktreeManager,
+    name: &str,
+) -> Result<bool> {
+    let Some(info) = manager.get_worktree_info(name)? else {
+        // Let remove_worktree report the missing worktree
+        return Ok(true);
+    };
+    if info.branch.is_empty() {
+        return Ok(true);
+    }
+
+    let base = repo.root_branch();
+    let unmerged = manager
+        .unmerged_commit_count(&info.branch, base)
+        .unwrap_or(0);
+    if unmerged == 0 {
+        return Ok(true);
+    }
+
+    eprintln!(
+        "Warning: branch '{}' has {} commit(s) not on '{}'.",
+        info.branch, unmerged, base
+    );
+    if !std::io::IsTerminal::is_terminal(&std::io::stderr()) {
+        anyhow::bail!(
+            "Refusing to remove worktree with unmerged commits. Pass --force to proceed."
+        );
+    }
+
+    Ok(Confirm::new()
+        .with_prompt(format!("Remove worktree '{}' anyway?", name))
+        .default(false)
+        .interact()?)
+}
+
+fn cmd_rm_all(repo: &RepoContext, pattern: Option<&str>, yes: bool) -> Result<()> {
+    let manager = WorktreeManager::new(repo.root.clone())?;
+    let (matched, skipped): (Vec<_>, Vec<_>) = manager
+        .list_managed(&repo.worktree_dir)?
+        .into_iter()
+        .partition(|wt| pattern.is_none_or(|p| glob_match(p, &wt.task_id)));
+
+    if matched.is_empty() {
+        match pattern {
+            Some(p) => eprintln!("No worktrees match '{}'.", p),
+            None => eprintln!("No worktrees found."),
+        }
+        return Ok(());
+    }
+
+    eprintln!(
+        "Matched {} worktree(s), skipped {}:",
+        matched.len(),
+        skipped.len()
+    );
+    for wt in &matched {
+        eprintln!("  {}", wt.task_id);
+    }
+
+    if !yes {
+        if !std::io::IsTerminal::is_terminal(&std::io::stderr()) {
+            anyhow::bail!(
+                "Refusing to remove worktrees without confirmation. Pass --yes to proceed."
+            );
+        }
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Remove {} worktree(s)?", matched.len()))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            return Ok(());
+        }
+    }
+
+    let mut failed = 0;
+    for wt in &matched {
+        let removed = pre_remove_hooks(repo, &manager, &wt.task_id)
+            .and_then(|()| manager.remove_worktree(&wt.task_id));
+        match removed {
+            Ok(()) => eprintln!("Removed worktree: {}", wt.task_id),
+            Err(error) => {
+                eprintln!("Failed to remove {}: {}", wt.task_id, error);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("Failed to remove {} worktree(s)", failed);
+    }
+    Ok(())
+}
+
+fn cmd_which(repo: &RepoContext, path: bool, branch: bool) -> Result<()> {
+    if path {
+        println!("{}", repo.checkout.display());
+    } else if branch {
+        println!("{}", Git::new(&repo.checkout).current_branch()?);
+    } else {
+        println!("{}", get_current_worktree_name(&repo.checkout)?);
+    }
+    Ok(())
+}
+
+/// Resolve `name`, or the current worktree when omitted, to its info.
+fn resolve_worktree(repo: &RepoContext, name: Option<String>, cmd: &str) -> Result<WorktreeInfo> {
+    let manager = WorktreeManager::new(repo.root.clone())?;
 
     let wt_name = match name {
         Some(n) => n,
         None => {
-            let current = get_current_worktree_name(&config.root)?;
+            let current = get_current_worktree_name(&repo.checkout)?;
             if current == "main" {
-                anyhow::bail!("Not in a worktree. Specify a worktree name: wt use <name>");
+                anyhow::bail!(
+                    "Not in a worktree. Specify a worktree name: wt {} <name>",
+                    cmd
+                );
             }
             current
         }
     };
 
-    let wt_info = worktrees
-        .iter()
-        .find(|w| w.task_id == wt_name)
-        .ok_or_else(|| anyhow::anyhow!("Worktree '{}' not found", wt_name))?;
+    manager
+        .get_worktree_info(&wt_name)?
+        .ok_or_else(|| anyhow::anyhow!("Worktree '{}' not found", wt_name))
+}
+
+fn cmd_open(repo: &RepoContext, name: Option<String>) -> Result<()> {
+    let wt_info = resolve_worktree(repo, name, "open")?;
+    let editor_cmd = &repo.config.session.editor_cmd;
+
+    // Run through the shell, as session mode does, so editor_cmd may
+    // carry arguments
+    let status = Command::new("sh")
+        .args(["-c", editor_cmd])
+        .current_dir(&wt_info.path)
+        .run_status()
+        .with_context(|| format!("Failed to run editor '{}'", editor_cmd))?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+fn cmd_sync(
+    repo: &RepoContext,
+    name: Option<String>,
+    base: Option<&str>,
+    rebase: bool,
+) -> Result<()> {
+    let wt_info = resolve_worktree(repo, name, "sync")?;
+    let base = base.unwrap_or_else(|| repo.root_branch());
+    let git = Git::new(&wt_info.path);
+
+    // A base with an upstream syncs from the freshly fetched upstream;
+    // offline, from whatever the remote-tracking ref last said
+    let base = match git.branch_remote(base) {
+        Some(remote) => {
+            if let Err(error) = git.run(["fetch", &remote]) {
+                eprintln!("wt: warning: {}", error);
+            }
+            git.upstream(base).unwrap_or_else(|| base.to_string())
+        }
+        None => base.to_string(),
+    };
+    let base = base.as_str();
+
+    let (ahead, behind) = git.ahead_behind(base)?;
+    if behind == 0 {
+        eprintln!("'{}' is up to date with '{}'.", wt_info.task_id, base);
+        return Ok(());
+    }
+
+    if ahead == 0 {
+        git.run(["merge", "--ff-only", base])?;
+        eprintln!(
+            "Fast-forwarded '{}' by {} commit(s) from '{}'.",
+            wt_info.task_id, behind, base
+        );
+    } else if rebase {
+        if let Err(error) = git.run(["rebase", base]) {
+            let _ = git.run(["rebase", "--abort"]);
+            anyhow::bail!(
+                "Rebasing '{}' onto '{}' failed; the branch is unchanged.\n{}",
+                wt_info.task_id,
+                base,
+                error
+            );
+        }
+        eprintln!(
+            "Rebased {} commit(s) of '{}' onto '{}'.",
+            ahead, wt_info.task_id, base
+        );
+    } else {
+        anyhow::bail!(
+            "'{}' has diverged from '{}' ({} ahead, {} behind); nothing was changed.\n\
+             Use 'wt sync --rebase' or merge it yourself.",
+            wt_info.task_id,
+            base,
+            ahead,
+            behind
+        );
+    }
+    Ok(())
+}
+
+fn cmd_diff(
+    repo: &RepoContext,
+    name: Option<String>,
+    base: Option<&str>,
+    stat: bool,
+) -> Result<()> {
+    let wt_info = resolve_worktree(repo, name, "diff")?;
+    if wt_info.branch.is_empty() {
+        anyhow::bail!("Worktree '{}' has a detached HEAD", wt_info.task_id);
+    }
+    let base = base.unwrap_or_else(|| repo.root_branch());
+
+    // Inherit stdio so git pages the output as it normally would
+    let range = format!("{}...{}", base, wt_info.branch);
+    let mut cmd = Command::new("git");
+    cmd.arg("diff");
+    if stat {
+        cmd.arg("--stat");
+    }
+    let status = cmd
+        .arg(&range)
+        .current_dir(&repo.root)
+        .run_status()
+        .context("Failed to run git diff")?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+fn cmd_use(repo: &RepoContext, name: Option<String>, print_path: bool) -> Result<()> {
+    let wt_info = resolve_worktree(repo, name, "use")?;
+
+    if print_path {
+        println!("{}", wt_info.path.display());
+        return Ok(());
+    }
 
-    spawn_wt_shell(&wt_info.path, &wt_info.task_id, &wt_info.branch)?;
+    spawn_wt_shell(
+        &wt_info.path,
+        &wt_info.task_id,
+        &wt_info.branch,
+        &repo.config.shell,
+    )?;
     Ok(())
 }
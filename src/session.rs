@@ -1,13 +1,21 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use crate::config::Config;
+use crate::git::Git;
 use crate::tmux_manager::TmuxManager;
 
+/// Schema version written to `sessions.json`. Files without a `version`
+/// key predate versioning and are treated as version 0.
+pub const STATE_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionState {
+    #[serde(default)]
+    pub version: u32,
     pub session_name: String,
     pub worktrees: HashMap<String, WindowInfo>,
     /// Windows-mode sessions keyed by worktree name. Empty for panes-only
@@ -21,6 +29,14 @@ pub struct WindowInfo {
     pub window_index: u32,
     pub pane_count: u8,
     pub worktree_path: PathBuf,
+    /// Set when the worktree was opened as a split of another window
+    /// (`session add --attach-here`) rather than in a window of its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_window: Option<String>,
+    /// Agent the window was opened with (`session add --agent`), run again
+    /// by `session restore` and `session restart`. `None` means `agent_cmd`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_cmd: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -33,41 +49,145 @@ pub struct WindowsSessionInfo {
 impl SessionState {
     pub fn new(session_name: &str) -> Self {
         Self {
+            version: STATE_VERSION,
             session_name: session_name.to_string(),
             worktrees: HashMap::new(),
             windows_sessions: HashMap::new(),
         }
     }
 
-    fn state_file_path() -> Result<PathBuf> {
-        let wt_dir = Config::ensure_wt_dir()?;
-        Ok(wt_dir.join("sessions.json"))
+    /// State file for the repo at `repo_root`: `sessions/<name>-<hash>.json`
+    /// in the data dir, so repos never overwrite each other's state.
+    fn state_file_path(repo_root: &Path) -> Result<PathBuf> {
+        let dir = Config::ensure_data_dir()?.join("sessions");
+        std::fs::create_dir_all(&dir).context("Failed to create sessions directory")?;
+        Ok(dir.join(state_file_name(&Git::new(repo_root).main_checkout())))
+    }
+
+    /// The single `sessions.json` shared by all repos before state was
+    /// kept per repo.
+    fn legacy_state_file_path() -> Result<PathBuf> {
+        Ok(Config::ensure_data_dir()?.join("sessions.json"))
     }
 
-    /// Load session state from ~/.wt/sessions.json
-    pub fn load() -> Result<Option<Self>> {
-        let path = Self::state_file_path()?;
+    /// Load the session state of the repo at `repo_root`.
+    pub fn load(repo_root: &Path) -> Result<Option<Self>> {
+        let path = Self::state_file_path(repo_root)?;
         if !path.exists() {
-            return Ok(None);
+            Self::migrate_legacy(repo_root, &path)?;
+            if !path.exists() {
+                return Ok(None);
+            }
         }
 
-        let contents = std::fs::read_to_string(&path).context("Failed to read sessions.json")?;
-        let state: SessionState =
-            serde_json::from_str(&contents).context("Failed to parse sessions.json")?;
+        Self::load_from(&path)
+    }
+
+    /// Read state from `path`. State from a newer wt is an error and stays
+    /// untouched; anything else unreadable is backed up and reset.
+    fn load_from(path: &Path) -> Result<Option<Self>> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if let Some(version) = stored_version(&contents).filter(|&v| v > STATE_VERSION) {
+            anyhow::bail!(
+                "{} was written by a newer wt (state version {}, this wt reads up to {}); upgrade wt to use it",
+                path.display(),
+                version,
+                STATE_VERSION
+            );
+        }
 
-        Ok(Some(state))
+        match Self::parse(&contents) {
+            Ok(state) => Ok(Some(state)),
+            Err(error) => {
+                // Unreadable state must not break every session command;
+                // keep a copy for inspection and start fresh.
+                let backup = backup_path(path);
+                let _ = std::fs::rename(path, &backup);
+                eprintln!(
+                    "wt: warning: could not parse {} ({}); moved it to {} and reset session state",
+                    path.display(),
+                    error,
+                    backup.display()
+                );
+                Ok(None)
+            }
+        }
     }
 
-    /// Save session state to ~/.wt/sessions.json
-    pub fn save(&self) -> Result<()> {
-        let path = Self::state_file_path()?;
+    /// Move this repo's entries out of the legacy shared `sessions.json`
+    /// into `path`. Other repos' entries stay behind until those repos
+    /// load their state, and the legacy file is removed once empty.
+    fn migrate_legacy(repo_root: &Path, path: &Path) -> Result<()> {
+        let legacy = Self::legacy_state_file_path()?;
+        let Ok(contents) = std::fs::read_to_string(&legacy) else {
+            return Ok(());
+        };
+        let Ok(mut shared) = Self::parse(&contents) else {
+            return Ok(());
+        };
+
+        let main = Git::new(repo_root).main_checkout();
+        let worktrees = worktree_paths(&main);
+        let ours = shared.split_off(|path| {
+            path.starts_with(&main) || path.starts_with(repo_root) || worktrees.contains(path)
+        });
+
+        if !ours.is_empty() {
+            ours.write_to(path)?;
+        }
+        if shared.is_empty() {
+            std::fs::remove_file(&legacy).context("Failed to remove sessions.json")?;
+        } else if !ours.is_empty() {
+            shared.write_to(&legacy)?;
+        }
+        Ok(())
+    }
+
+    /// Deserialize state from any known schema version and upgrade it to
+    /// `STATE_VERSION`.
+    fn parse(contents: &str) -> Result<Self> {
+        let mut state: SessionState = serde_json::from_str(contents)?;
+        if state.version > STATE_VERSION {
+            anyhow::bail!(
+                "written by a newer wt (version {}, expected at most {})",
+                state.version,
+                STATE_VERSION
+            );
+        }
+        // v0 -> v1 only added fields with serde defaults
+        state.version = STATE_VERSION;
+        Ok(state)
+    }
+
+    /// Save the session state of the repo at `repo_root`.
+    pub fn save(&self, repo_root: &Path) -> Result<()> {
+        self.write_to(&Self::state_file_path(repo_root)?)
+    }
+
+    fn write_to(&self, path: &Path) -> Result<()> {
         let contents =
             serde_json::to_string_pretty(self).context("Failed to serialize session state")?;
 
-        std::fs::write(&path, contents).context("Failed to write sessions.json")?;
+        write_atomic(path, contents.as_bytes())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
         Ok(())
     }
 
+    /// Move the entries whose worktree path satisfies `owns` into a new
+    /// state with the same session name.
+    fn split_off(&mut self, owns: impl Fn(&Path) -> bool) -> Self {
+        let mut split = Self::new(&self.session_name);
+        (split.worktrees, self.worktrees) = std::mem::take(&mut self.worktrees)
+            .into_iter()
+            .partition(|(_, info)| owns(&info.worktree_path));
+        (split.windows_sessions, self.windows_sessions) =
+            std::mem::take(&mut self.windows_sessions)
+                .into_iter()
+                .partition(|(_, info)| owns(&info.worktree_path));
+        split
+    }
+
     /// Add a worktree window to the session
     pub fn add_worktree(&mut self, name: &str, window_index: u32, pane_count: u8, path: PathBuf) {
         self.worktrees.insert(
@@ -76,15 +196,56 @@ impl SessionState {
                 window_index,
                 pane_count,
                 worktree_path: path,
+                host_window: None,
+                agent_cmd: None,
+            },
+        );
+    }
+
+    /// Add a worktree that shares `host_window` instead of owning a window.
+    pub fn add_split_worktree(
+        &mut self,
+        name: &str,
+        host_window: &str,
+        window_index: u32,
+        path: PathBuf,
+    ) {
+        self.worktrees.insert(
+            name.to_string(),
+            WindowInfo {
+                window_index,
+                pane_count: 1,
+                worktree_path: path,
+                host_window: Some(host_window.to_string()),
+                agent_cmd: None,
             },
         );
     }
 
+    /// Record the agent `name` was opened with, or `None` for `agent_cmd`.
+    pub fn set_agent_cmd(&mut self, name: &str, agent_cmd: Option<String>) {
+        if let Some(info) = self.worktrees.get_mut(name) {
+            info.agent_cmd = agent_cmd;
+        }
+    }
+
     /// Remove a worktree from the session
     pub fn remove_worktree(&mut self, name: &str) -> Option<WindowInfo> {
         self.worktrees.remove(name)
     }
 
+    /// Move a worktree entry to a new name, returning false if `old` was
+    /// not tracked.
+    pub fn rename_worktree(&mut self, old: &str, new: &str) -> bool {
+        match self.worktrees.remove(old) {
+            Some(info) => {
+                self.worktrees.insert(new.to_string(), info);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Get worktree info by name
     pub fn get_worktree(&self, name: &str) -> Option<&WindowInfo> {
         self.worktrees.get(name)
@@ -95,27 +256,40 @@ impl SessionState {
         self.worktrees.contains_key(name)
     }
 
-    /// Sync session state with actual tmux windows.
+    /// Sync session state with actual tmux windows: drop entries whose
+    /// window is gone and record the live window index (a split takes its
+    /// host's) and pane count.
     pub fn sync_with_tmux(&mut self, tmux: &TmuxManager) -> Result<()> {
         let windows = tmux.list_windows()?;
-        let window_names: HashSet<_> = windows.iter().map(|window| window.name.clone()).collect();
+        let window_indices: HashMap<_, _> = windows
+            .iter()
+            .map(|window| (window.name.clone(), window.index))
+            .collect();
 
-        self.worktrees.retain(|name, _| window_names.contains(name));
+        self.worktrees.retain(|name, info| {
+            window_indices.contains_key(info.host_window.as_ref().unwrap_or(name))
+        });
 
+        for (name, info) in self.worktrees.iter_mut() {
+            info.window_index = window_indices[info.host_window.as_ref().unwrap_or(name)];
+        }
         for window in &windows {
             if let Some(info) = self.worktrees.get_mut(&window.name) {
-                info.pane_count = window.pane_count as u8;
+                if info.host_window.is_none() {
+                    info.pane_count = window.pane_count as u8;
+                }
             }
         }
 
         Ok(())
     }
 
-    /// Clear the session state file.
-    pub fn clear() -> Result<()> {
-        let path = Self::state_file_path()?;
+    /// Remove the session state file of the repo at `repo_root`.
+    pub fn clear(repo_root: &Path) -> Result<()> {
+        let path = Self::state_file_path(repo_root)?;
         if path.exists() {
-            std::fs::remove_file(&path).context("Failed to remove sessions.json")?;
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
         }
         Ok(())
     }
@@ -149,10 +323,129 @@ pub fn retain_live_sessions(
     entries.retain(|_, info| live.contains(&info.session_name));
 }
 
+/// The `version` key of serialized state, if it has one.
+fn stored_version(contents: &str) -> Option<u32> {
+    let value: serde_json::Value = serde_json::from_str(contents).ok()?;
+    value.get("version")?.as_u64()?.try_into().ok()
+}
+
+/// `<path>.<unix millis>.bak`, so repeated resets keep every copy.
+fn backup_path(path: &Path) -> PathBuf {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.bak", millis));
+    let mut backup = path.with_file_name(&name);
+    // Two resets within a millisecond
+    let mut n = 1;
+    while backup.exists() {
+        backup = path.with_file_name(format!("{}.{}", name.to_string_lossy(), n));
+        n += 1;
+    }
+    backup
+}
+
+/// Paths of every worktree of the repo at `repo_root`, from
+/// `git worktree list`. Empty if git fails.
+fn worktree_paths(repo_root: &Path) -> HashSet<PathBuf> {
+    let list = Git::new(repo_root).worktree_list().unwrap_or_default();
+    list.lines()
+        .filter_map(|line| line.strip_prefix("worktree "))
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// `<dir name>-<hash of the full path>.json`: readable, but distinct for
+/// repos that share a directory name.
+fn state_file_name(repo_root: &Path) -> String {
+    let name = repo_root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "repo".to_string());
+    // FNV-1a; unlike DefaultHasher it is stable across Rust releases
+    let hash = repo_root
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{}-{:016x}.json", name, hash)
+}
+
+/// Write `contents` to a temp file next to `path` and rename it into
+/// place, so a crash mid-write leaves either the old file or the new one.
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".tmp.{}", std::process::id()));
+    let tmp = path.with_file_name(tmp_name);
+
+    let result = std::fs::File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_off_moves_entries_by_worktree_path() {
+        let mut shared = SessionState::new("wt");
+        shared.add_worktree("a", 1, 2, PathBuf::from("/repos/one/.worktrees/a"));
+        shared.add_worktree("b", 2, 2, PathBuf::from("/repos/two/.worktrees/b"));
+        shared.add_windows_session(
+            "c",
+            WindowsSessionInfo {
+                session_name: "wt-c".to_string(),
+                worktree_path: PathBuf::from("/repos/one/.worktrees/c"),
+                windows: vec!["agent".to_string()],
+            },
+        );
+
+        let ours = shared.split_off(|path| path.starts_with("/repos/one"));
+
+        assert_eq!(ours.session_name, "wt");
+        assert!(ours.has_worktree("a"));
+        assert!(ours.windows_sessions.contains_key("c"));
+        assert!(!ours.has_worktree("b"));
+        assert!(shared.has_worktree("b"));
+        assert!(!shared.has_worktree("a"));
+        assert!(shared.windows_sessions.is_empty());
+    }
+
+    #[test]
+    fn test_state_file_name_distinguishes_same_named_repos() {
+        let one = state_file_name(Path::new("/work/app"));
+        let two = state_file_name(Path::new("/personal/app"));
+
+        assert!(one.starts_with("app-") && one.ends_with(".json"), "{}", one);
+        assert_ne!(one, two);
+        assert_eq!(one, state_file_name(Path::new("/work/app")));
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_file_without_leftovers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sessions.json");
+        std::fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+    }
+
     #[test]
     fn test_session_state_new() {
         let state = SessionState::new("wt");
@@ -177,6 +470,54 @@ mod tests {
         assert!(!state.has_worktree("feature-1"));
     }
 
+    #[test]
+    fn test_split_worktree_round_trips() {
+        let mut state = SessionState::new("wt");
+        state.add_split_worktree(
+            "feature-2",
+            "feature-1",
+            1,
+            PathBuf::from("/path/to/feature-2"),
+        );
+
+        let json = serde_json::to_string(&state).unwrap();
+        let loaded = SessionState::parse(&json).unwrap();
+        let info = loaded.get_worktree("feature-2").unwrap();
+        assert_eq!(info.host_window.as_deref(), Some("feature-1"));
+        assert_eq!(info.window_index, 1);
+
+        // Entries without a host omit the field entirely
+        state.add_worktree("feature-1", 1, 2, PathBuf::from("/path/to/feature-1"));
+        let json = serde_json::to_string(&state.worktrees["feature-1"]).unwrap();
+        assert!(!json.contains("host_window"));
+    }
+
+    #[test]
+    fn test_agent_cmd_round_trips() {
+        let mut state = SessionState::new("wt");
+        state.add_worktree("feature-1", 1, 2, PathBuf::from("/path/to/feature-1"));
+        let json = serde_json::to_string(&state.worktrees["feature-1"]).unwrap();
+        assert!(!json.contains("agent_cmd"));
+
+        state.set_agent_cmd("feature-1", Some("aider".to_string()));
+        let json = serde_json::to_string(&state).unwrap();
+        let loaded = SessionState::parse(&json).unwrap();
+        let info = loaded.get_worktree("feature-1").unwrap();
+        assert_eq!(info.agent_cmd.as_deref(), Some("aider"));
+    }
+
+    #[test]
+    fn test_rename_worktree() {
+        let mut state = SessionState::new("wt");
+        state.add_worktree("feature-1", 1, 2, PathBuf::from("/path/to/feature-1"));
+
+        assert!(state.rename_worktree("feature-1", "feature-2"));
+        assert!(!state.has_worktree("feature-1"));
+        assert_eq!(state.get_worktree("feature-2").unwrap().window_index, 1);
+
+        assert!(!state.rename_worktree("missing", "other"));
+    }
+
     #[test]
     fn test_serialize_deserialize() {
         let mut state = SessionState::new("wt");
@@ -223,6 +564,59 @@ mod tests {
         assert_eq!(loaded.windows_sessions, state.windows_sessions);
     }
 
+    #[test]
+    fn test_parse_migrates_v0_state() {
+        let v0 = r#"{
+            "session_name": "wt",
+            "worktrees": {
+                "feature": {
+                    "window_index": 1,
+                    "pane_count": 2,
+                    "worktree_path": "/path/to/feature"
+                }
+            }
+        }"#;
+
+        let state = SessionState::parse(v0).unwrap();
+        assert_eq!(state.version, STATE_VERSION);
+        assert!(state.has_worktree("feature"));
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_shapes() {
+        assert!(SessionState::parse("{\"worktrees\": []}").is_err());
+        assert!(SessionState::parse("not json").is_err());
+
+        let newer = format!(
+            r#"{{"version": {}, "session_name": "wt", "worktrees": {{}}}}"#,
+            STATE_VERSION + 1
+        );
+        assert!(SessionState::parse(&newer).is_err());
+    }
+
+    #[test]
+    fn test_load_from_keeps_newer_state_and_backs_up_garbage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repo.json");
+
+        let newer = format!(
+            r#"{{"version": {}, "session_name": "wt", "worktrees": []}}"#,
+            STATE_VERSION + 1
+        );
+        std::fs::write(&path, &newer).unwrap();
+        let error = SessionState::load_from(&path).unwrap_err();
+        assert!(error.to_string().contains("newer wt"), "{}", error);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), newer);
+
+        for _ in 0..2 {
+            std::fs::write(&path, "not json").unwrap();
+            assert!(SessionState::load_from(&path).unwrap().is_none());
+            assert!(!path.exists());
+        }
+        let backups = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(backups, 2);
+    }
+
     #[test]
     fn test_deserialize_legacy_state_without_windows_sessions() {
         let legacy = r#"{
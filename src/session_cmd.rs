@@ -1,16 +1,25 @@
 use anyhow::Result;
-use clap::Subcommand;
-use dialoguer::Select;
+use clap::{Subcommand, ValueEnum};
+use dialoguer::{Confirm, Select};
+use std::collections::HashMap;
 use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::{cmd_ls, RepoConfig};
-use wt::config::{Config, SessionMode};
+use crate::{cmd_ls, run_post_create_hook, RepoContext};
+use wt::command::CommandExt;
+use wt::config::{Config, SessionConfig, SessionMode};
 use wt::session::{retain_live_sessions, SessionState, WindowsSessionInfo};
-use wt::tmux_manager::{AgentStatus, TmuxManager};
-use wt::worktree_manager::{check_not_in_worktree, ensure_worktrees_in_gitignore, WorktreeManager};
+use wt::tmux_manager::{AgentStatus, TmuxManager, TmuxWindow, AGENT_LOG_FILE};
+use wt::worktree_manager::{
+    check_not_in_worktree, ensure_git_excluded, ensure_worktrees_in_gitignore, WorktreeInfo,
+    WorktreeManager,
+};
 
 const SESSION_NAME: &str = "wt";
+/// Name of the `--watch` status window. `~` can't appear in a branch
+/// name, so no worktree window can collide with it.
+const STATUS_WINDOW: &str = "wt~status";
 const NO_WINDOWS_SESSIONS_MSG: &str =
     "No worktree sessions found. Use 'wt session add <name>' to create one.";
 
@@ -18,35 +27,139 @@ const NO_WINDOWS_SESSIONS_MSG: &str =
 pub(crate) enum SessionAction {
     /// List worktrees in the session
     Ls,
-    /// Add a worktree to the session
+    /// Add one or more worktrees to the session
     Add {
-        /// Name for the worktree
-        name: String,
-        /// Base branch to create from
-        #[arg(short, default_value = "main")]
-        base: String,
-        /// Override pane count (2 or 3)
+        /// Names of the worktrees; each gets its own window
+        #[arg(required = true)]
+        names: Vec<String>,
+        /// Base branch to create from (defaults to the repo's default branch)
+        #[arg(short)]
+        base: Option<String>,
+        /// Override pane count (1, 2 or 3)
         #[arg(long)]
         panes: Option<u8>,
         /// Create status window with live agent status
         #[arg(long)]
         watch: bool,
+        /// Prompt to send to the agent once it has started
+        #[arg(long)]
+        prompt: Option<String>,
+        /// Wait until the agent pane shows this text before sending --prompt
+        #[arg(long, value_name = "PATTERN", requires = "prompt")]
+        wait_ready: Option<String>,
+        /// Arrange the panes with a tmux layout (e.g. main-vertical) or give
+        /// the agent pane N% of the width (e.g. 70%)
+        #[arg(long)]
+        layout: Option<String>,
+        /// Run this instead of agent_cmd in the new windows (kept for restart and restore)
+        #[arg(long, value_name = "CMD")]
+        agent: Option<String>,
+        /// Run this instead of editor_cmd in the new windows (3-pane layouts)
+        #[arg(long, value_name = "CMD")]
+        editor: Option<String>,
+        /// Open the worktree as a split of the current window instead of a new window
+        #[arg(long, conflicts_with_all = ["panes", "watch", "layout"])]
+        attach_here: bool,
+        /// Create the window without switching to or attaching the session (for scripts)
+        #[arg(long, conflicts_with = "attach_here")]
+        no_attach: bool,
     },
     /// Remove a worktree from the session
     Rm {
         /// Name of the worktree to remove
         name: String,
     },
+    /// Rename a worktree window in the session
+    Rename {
+        /// Current window name
+        old: String,
+        /// New window name
+        new: String,
+    },
+    /// Interrupt the agent in a worktree window and start it again
+    Restart {
+        /// Name of the worktree window
+        name: String,
+        /// Prompt to send to the agent once it has restarted
+        #[arg(long)]
+        prompt: Option<String>,
+        /// Wait until the agent pane shows this text before sending --prompt
+        #[arg(long, value_name = "PATTERN", requires = "prompt")]
+        wait_ready: Option<String>,
+    },
+    /// Recreate session windows recorded in saved state (e.g. after a reboot)
+    Restore,
+    /// Reconcile saved state with the windows actually open in tmux
+    Sync,
+    /// Send a command to every worktree window in the session
+    Broadcast {
+        /// Pane index to send to (0 is the agent pane)
+        #[arg(long, default_value = "0")]
+        pane: u32,
+        /// Ask for confirmation before sending
+        #[arg(long)]
+        confirm: bool,
+        /// Command to send, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
     /// Watch session status (live-updating display)
     Watch {
         /// Refresh interval in seconds
         #[arg(short, default_value = "2")]
         interval: u64,
+        /// Order windows by index or by agent status (stopped, then active first)
+        #[arg(long, value_enum, default_value = "index")]
+        sort: WatchSort,
+        /// Only show windows with this agent status (repeatable)
+        #[arg(long, value_enum)]
+        only: Vec<AgentStatus>,
+        /// Ring the bell (and run session.notify_cmd) when an agent goes idle
+        #[arg(long)]
+        notify: bool,
+        /// Print one JSON array of windows per refresh instead of the dashboard
+        #[arg(long, conflicts_with = "notify")]
+        json: bool,
     },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum WatchSort {
+    Index,
+    Status,
+}
+
+struct SessionAddOptions {
+    base: Option<String>,
+    panes: Option<u8>,
+    layout: Option<String>,
+    watch: bool,
+    prompt: Option<String>,
+    wait_ready: Option<String>,
+    attach_here: bool,
+    no_attach: bool,
+    /// `--agent`, recorded in session state for restore and restart.
+    agent: Option<String>,
+}
+
+impl SessionAddOptions {
+    /// `-b`, or the repo's default branch when it wasn't given.
+    fn base<'a>(&'a self, context: &'a SessionCmdContext<'_>) -> &'a str {
+        self.base
+            .as_deref()
+            .unwrap_or_else(|| context.repo.root_branch())
+    }
+
+    /// `--layout`, or the configured `layout`.
+    fn layout<'a>(&'a self, context: &'a SessionCmdContext<'_>) -> Option<&'a str> {
+        self.layout
+            .as_deref()
+            .or(context.config.session.layout.as_deref())
+    }
+}
+
 struct SessionCmdContext<'a> {
-    repo: &'a RepoConfig,
+    repo: &'a RepoContext,
     config: Config,
     mode: SessionMode,
 }
@@ -61,20 +174,32 @@ struct SessionRmProbe {
 }
 
 impl<'a> SessionCmdContext<'a> {
-    fn new(repo: &'a RepoConfig, mode_override: Option<SessionMode>) -> Self {
-        let config = Config::load_for_repo(&repo.root);
+    fn new(repo: &'a RepoContext, mode_override: Option<SessionMode>) -> Self {
+        let config = repo.config.clone();
         let mode = mode_override.unwrap_or(config.session.mode);
 
         Self { repo, config, mode }
     }
 
+    /// Use `agent` and `editor` instead of the configured commands for
+    /// whatever this invocation sets up.
+    fn with_commands(mut self, agent: Option<String>, editor: Option<String>) -> Self {
+        if let Some(agent) = agent {
+            self.config.session.agent_cmd = agent;
+        }
+        if let Some(editor) = editor {
+            self.config.session.editor_cmd = editor;
+        }
+        self
+    }
+
     fn effective_panes(&self, panes_override: Option<u8>) -> u8 {
         self.config.effective_panes(panes_override)
     }
 }
 
 pub(crate) fn run_session(
-    repo: &RepoConfig,
+    repo: &RepoContext,
     mode_override: Option<SessionMode>,
     action: Option<SessionAction>,
 ) -> Result<()> {
@@ -84,39 +209,158 @@ pub(crate) fn run_session(
     }
 
     let context = SessionCmdContext::new(repo, mode_override);
+    if context.mode == SessionMode::Panes {
+        check_panes_session_owner(&context)?;
+    }
 
     match action {
         None => match context.mode {
             SessionMode::Panes => {
-                let tmux = panes_tmux();
-                cmd_session_attach(&tmux)
+                let tmux = panes_tmux(&context);
+                cmd_session_attach(&context, &tmux)
             }
-            SessionMode::Windows => cmd_session_attach_windows(),
+            SessionMode::Windows => cmd_session_attach_windows(&context.repo.root),
         },
         Some(SessionAction::Ls) => match context.mode {
             SessionMode::Panes => {
-                let tmux = panes_tmux();
+                let tmux = panes_tmux(&context);
                 cmd_session_ls(&tmux)
             }
-            SessionMode::Windows => cmd_session_ls_windows(),
+            SessionMode::Windows => {
+                cmd_session_ls_windows(&context.repo.root, &context.config.session)
+            }
         },
         Some(SessionAction::Add {
-            name,
+            names,
             base,
             panes,
+            layout,
+            agent,
+            editor,
             watch,
-        }) => match context.mode {
-            SessionMode::Panes => cmd_session_add_panes(&context, &name, &base, panes, watch),
-            SessionMode::Windows => cmd_session_add_windows(&context, &name, &base, panes, watch),
-        },
+            prompt,
+            wait_ready,
+            attach_here,
+            no_attach,
+        }) => {
+            let context = context.with_commands(agent.clone(), editor);
+            let options = SessionAddOptions {
+                base,
+                panes,
+                layout,
+                watch,
+                prompt,
+                wait_ready,
+                attach_here,
+                no_attach,
+                agent,
+            };
+            cmd_session_add(&context, &names, &options)
+        }
         Some(SessionAction::Rm { name }) => match context.mode {
             SessionMode::Panes => cmd_session_rm_panes(&context, &name),
             SessionMode::Windows => cmd_session_rm_windows(&context, &name),
         },
-        Some(SessionAction::Watch { interval }) => match context.mode {
+        Some(SessionAction::Rename { old, new }) => match context.mode {
+            SessionMode::Panes => {
+                let tmux = panes_tmux(&context);
+                cmd_session_rename(&context.repo.root, &tmux, &old, &new)
+            }
+            SessionMode::Windows => {
+                eprintln!(
+                    "'wt session rename' is not yet supported in windows mode. \
+                     Windows-mode sessions are named after their worktree."
+                );
+                Ok(())
+            }
+        },
+        Some(SessionAction::Restart {
+            name,
+            prompt,
+            wait_ready,
+        }) => match context.mode {
+            SessionMode::Panes => {
+                let agent = SessionState::load(&context.repo.root)?
+                    .and_then(|state| state.get_worktree(&name)?.agent_cmd.clone());
+                let context = context.with_commands(agent, None);
+                let tmux = panes_tmux(&context);
+                cmd_session_restart(
+                    &context,
+                    &tmux,
+                    &name,
+                    prompt.as_deref(),
+                    wait_ready.as_deref(),
+                )
+            }
+            SessionMode::Windows => {
+                eprintln!(
+                    "'wt session restart' is not yet supported in windows mode. \
+                     Attach with 'wt session' and restart the agent by hand."
+                );
+                Ok(())
+            }
+        },
+        Some(SessionAction::Restore) => match context.mode {
+            SessionMode::Panes => {
+                let tmux = panes_tmux(&context);
+                cmd_session_restore(&context, &tmux)
+            }
+            SessionMode::Windows => {
+                eprintln!(
+                    "'wt session restore' is not yet supported in windows mode. \
+                     Use 'wt session --mode windows add <name>' to recreate a session."
+                );
+                Ok(())
+            }
+        },
+        Some(SessionAction::Sync) => match context.mode {
+            SessionMode::Panes => {
+                let tmux = panes_tmux(&context);
+                cmd_session_sync(&context, &tmux)
+            }
+            SessionMode::Windows => {
+                eprintln!(
+                    "'wt session sync' is not yet supported in windows mode. \
+                     Windows-mode state is pruned automatically by 'wt session ls'."
+                );
+                Ok(())
+            }
+        },
+        Some(SessionAction::Broadcast {
+            pane,
+            confirm,
+            command,
+        }) => match context.mode {
+            SessionMode::Panes => {
+                let tmux = panes_tmux(&context);
+                cmd_session_broadcast(&tmux, pane, confirm, &command.join(" "))
+            }
+            SessionMode::Windows => {
+                eprintln!(
+                    "'wt session broadcast' is not yet supported in windows mode. \
+                     Use 'wt session ls' to inspect status per session."
+                );
+                Ok(())
+            }
+        },
+        Some(SessionAction::Watch {
+            interval,
+            sort,
+            only,
+            notify,
+            json,
+        }) => match context.mode {
             SessionMode::Panes => {
-                let tmux = panes_tmux();
-                cmd_session_watch(&tmux, interval)
+                let tmux = panes_tmux(&context);
+                cmd_session_watch(
+                    &tmux,
+                    interval,
+                    sort,
+                    &only,
+                    notify,
+                    json,
+                    &context.config.session,
+                )
             }
             SessionMode::Windows => {
                 eprintln!(
@@ -134,10 +378,13 @@ fn ensure_worktree_path(
     name: &str,
     base: &str,
 ) -> Result<PathBuf> {
-    check_not_in_worktree(&context.repo.root)?;
+    check_not_in_worktree(&context.repo.checkout, &context.repo.worktree_dir)?;
 
     let manager = WorktreeManager::new(context.repo.root.clone())?;
     ensure_worktrees_in_gitignore(&context.repo.root, &context.repo.worktree_dir)?;
+    if context.config.session.log_agent_output {
+        ensure_git_excluded(&context.repo.root, AGENT_LOG_FILE)?;
+    }
     std::fs::create_dir_all(&context.repo.worktree_dir)?;
 
     match manager.get_worktree_info(name)? {
@@ -147,38 +394,57 @@ fn ensure_worktree_path(
         }
         None => {
             eprintln!("Creating worktree: {}", name);
-            manager.create_worktree(name, base, &context.repo.worktree_dir, |remotes| {
-                choose_remote_branch(name, remotes)
-            })
+            let path =
+                manager.create_worktree(name, base, &context.repo.worktree_dir, |remotes| {
+                    choose_remote_branch(name, remotes)
+                })?;
+            run_post_create_hook(&context.repo.root, name, &path);
+            Ok(path)
         }
     }
 }
 
-fn choose_remote_branch(name: &str, remotes: &[String]) -> Result<String> {
-    if remotes.is_empty() {
-        anyhow::bail!("No remote branches match '{}'.", name);
-    }
-
-    if remotes.len() == 1 {
-        return Ok(remotes[0].clone());
-    }
-
+/// Ask which of several remote branches named `name` to track. `None` if
+/// the prompt is cancelled or there is no terminal to ask on.
+fn choose_remote_branch(name: &str, remotes: &[String]) -> Option<String> {
     let selection = Select::new()
         .with_prompt(format!("Select remote branch for '{}'", name))
         .items(remotes)
         .default(0)
-        .interact()?;
+        .interact_opt()
+        .ok()??;
 
-    Ok(remotes[selection].clone())
+    remotes.get(selection).cloned()
 }
 
-fn panes_tmux() -> TmuxManager {
+fn panes_tmux(context: &SessionCmdContext<'_>) -> TmuxManager {
     TmuxManager::new(SESSION_NAME)
+        .with_agent_program(context.config.session.agent_program())
+        .with_owner(&context.repo.root)
+}
+
+/// Panes mode has one `wt` tmux session per tmux server, but session state
+/// is kept per repo. Refuse to mix this repo's windows into a session made
+/// for another repo, where syncing would prune each other's state.
+fn check_panes_session_owner(context: &SessionCmdContext<'_>) -> Result<()> {
+    let tmux = panes_tmux(context);
+    if !tmux.session_exists()? {
+        return Ok(());
+    }
+    match tmux.session_owner()? {
+        Some(owner) if owner != context.repo.root => anyhow::bail!(
+            "The '{}' tmux session belongs to {}; panes mode keeps one session per tmux server.\n\
+             Finish or kill that session first, or use --mode windows for this repo.",
+            SESSION_NAME,
+            owner.display()
+        ),
+        _ => Ok(()),
+    }
 }
 
 fn create_status_window_session(tmux: &TmuxManager, repo_root: &Path) -> Result<()> {
-    tmux.create_session("status", repo_root)?;
-    tmux.send_keys("status", 0, "wt session watch")?;
+    tmux.create_session(STATUS_WINDOW, repo_root)?;
+    tmux.send_keys(STATUS_WINDOW, 0, "wt session watch")?;
     Ok(())
 }
 
@@ -186,18 +452,18 @@ fn ensure_status_window(tmux: &TmuxManager, repo_root: &Path) -> Result<()> {
     if tmux
         .list_windows()?
         .iter()
-        .any(|window| window.name == "status")
+        .any(|window| window.name == STATUS_WINDOW)
     {
         return Ok(());
     }
 
-    tmux.create_window("status", repo_root)?;
-    tmux.send_keys("status", 0, "wt session watch")?;
+    tmux.create_window(STATUS_WINDOW, repo_root)?;
+    tmux.send_keys(STATUS_WINDOW, 0, "wt session watch")?;
     Ok(())
 }
 
-fn cmd_session_attach(tmux: &TmuxManager) -> Result<()> {
-    if !tmux.session_exists()? {
+fn cmd_session_attach(context: &SessionCmdContext<'_>, tmux: &TmuxManager) -> Result<()> {
+    if !tmux.session_exists()? && !build_session_from_worktrees(context, tmux)? {
         eprintln!("No session found. Use 'wt session add <name>' to create one.");
         return Ok(());
     }
@@ -207,7 +473,208 @@ fn cmd_session_attach(tmux: &TmuxManager) -> Result<()> {
         return Ok(());
     }
 
-    tmux.enter()
+    tmux.enter()?;
+    Ok(())
+}
+
+/// Offer to create the panes session with one window per existing
+/// worktree. Returns false when there is nothing to build or the user
+/// declines; never builds without a TTY to confirm on.
+fn build_session_from_worktrees(
+    context: &SessionCmdContext<'_>,
+    tmux: &TmuxManager,
+) -> Result<bool> {
+    let manager = WorktreeManager::new(context.repo.root.clone())?;
+    let worktrees: Vec<_> = manager
+        .list_managed(&context.repo.worktree_dir)?
+        .into_iter()
+        .filter(|wt| wt.path.exists())
+        .collect();
+
+    if worktrees.is_empty() || !std::io::stderr().is_terminal() {
+        return Ok(false);
+    }
+
+    let confirmed = Confirm::new()
+        .with_prompt(format!(
+            "No session found. Create one from {} existing worktree(s)?",
+            worktrees.len()
+        ))
+        .default(true)
+        .interact()?;
+    if !confirmed {
+        return Ok(false);
+    }
+
+    eprintln!("Creating tmux session: {}", SESSION_NAME);
+    let panes = context.effective_panes(None);
+    let mut state =
+        SessionState::load(&context.repo.root)?.unwrap_or_else(|| SessionState::new(SESSION_NAME));
+    for wt in &worktrees {
+        let index = add_layout_window(context, tmux, &wt.task_id, &wt.path, panes, None)?;
+        state.add_worktree(&wt.task_id, index, panes, wt.path.clone());
+    }
+    state.sync_with_tmux(tmux)?;
+    state.save(&context.repo.root)?;
+
+    Ok(true)
+}
+
+/// Add a worktree window with its pane layout, creating the session
+/// around it if this is the first window. `agent_cmd` replaces the
+/// configured agent for this window. Returns the new window's index.
+fn add_layout_window(
+    context: &SessionCmdContext<'_>,
+    tmux: &TmuxManager,
+    name: &str,
+    path: &Path,
+    panes: u8,
+    agent_cmd: Option<&str>,
+) -> Result<u32> {
+    let index = if tmux.session_exists()? {
+        tmux.create_window(name, path)?
+    } else {
+        tmux.create_session(name, path)?;
+        tmux.list_windows()?
+            .iter()
+            .find(|window| window.name == name)
+            .map_or(0, |window| window.index)
+    };
+    let mut config = context.config.session.clone();
+    if let Some(agent_cmd) = agent_cmd {
+        config.agent_cmd = agent_cmd.to_string();
+    }
+    tmux.setup_worktree_layout(name, path, panes, config.layout.as_deref(), &config)?;
+    eprintln!("Added window: {} ({} panes)", name, panes);
+    Ok(index)
+}
+
+fn cmd_session_restore(context: &SessionCmdContext<'_>, tmux: &TmuxManager) -> Result<()> {
+    let Some(mut state) = SessionState::load(&context.repo.root)? else {
+        eprintln!("No saved session state to restore.");
+        return Ok(());
+    };
+
+    let existing: Vec<String> = tmux
+        .list_windows()?
+        .into_iter()
+        .map(|window| window.name)
+        .collect();
+
+    let mut names: Vec<_> = state.worktrees.keys().cloned().collect();
+    names.sort_by_key(|name| state.worktrees[name].window_index);
+
+    let mut restored = 0;
+    for name in names {
+        let info = state.worktrees[&name].clone();
+        if !info.worktree_path.exists() {
+            eprintln!(
+                "Pruning {}: {} no longer exists",
+                name,
+                info.worktree_path.display()
+            );
+            state.remove_worktree(&name);
+            continue;
+        }
+        if existing.contains(info.host_window.as_ref().unwrap_or(&name)) {
+            continue;
+        }
+
+        // A split whose host window is gone comes back in a window of its own
+        let panes = match info.host_window {
+            Some(_) => context.effective_panes(None),
+            None => info.pane_count,
+        };
+        let index = add_layout_window(
+            context,
+            tmux,
+            &name,
+            &info.worktree_path,
+            panes,
+            info.agent_cmd.as_deref(),
+        )?;
+        state.add_worktree(&name, index, panes, info.worktree_path);
+        state.set_agent_cmd(&name, info.agent_cmd);
+        restored += 1;
+    }
+
+    if tmux.session_exists()? {
+        state.sync_with_tmux(tmux)?;
+    }
+    save_state_or_clear_if_empty(&context.repo.root, &state)?;
+
+    if restored == 0 {
+        eprintln!("Nothing to restore.");
+        return Ok(());
+    }
+
+    eprintln!("Restored {} window(s).", restored);
+    if tmux.is_inside_session() {
+        Ok(())
+    } else {
+        tmux.enter()?;
+        Ok(())
+    }
+}
+
+fn cmd_session_sync(context: &SessionCmdContext<'_>, tmux: &TmuxManager) -> Result<()> {
+    if !tmux.session_exists()? {
+        eprintln!("No session found.");
+        return Ok(());
+    }
+
+    let mut state =
+        SessionState::load(&context.repo.root)?.unwrap_or_else(|| SessionState::new(SESSION_NAME));
+    let mut removed: Vec<String> = state.worktrees.keys().cloned().collect();
+    state.sync_with_tmux(tmux)?;
+    removed.retain(|name| !state.has_worktree(name));
+    removed.sort();
+
+    // Windows opened by hand are adopted if their first pane sits in a
+    // managed worktree
+    let manager = WorktreeManager::new(context.repo.root.clone())?;
+    let worktrees = manager.list_managed(&context.repo.worktree_dir)?;
+    let mut added = Vec::new();
+    for window in tmux.list_windows()? {
+        if window.name == STATUS_WINDOW || state.has_worktree(&window.name) {
+            continue;
+        }
+        let Ok(cwd) = tmux.pane_current_path(&window.name, 0) else {
+            continue;
+        };
+        if let Some(wt) = worktree_containing(&worktrees, &cwd) {
+            state.add_worktree(
+                &window.name,
+                window.index,
+                window.pane_count as u8,
+                wt.path.clone(),
+            );
+            added.push(format!("{} ({})", window.name, wt.path.display()));
+        }
+    }
+
+    save_state_or_clear_if_empty(&context.repo.root, &state)?;
+
+    for name in &removed {
+        eprintln!("Removed: {} (window no longer exists)", name);
+    }
+    for entry in &added {
+        eprintln!("Added: {}", entry);
+    }
+    if removed.is_empty() && added.is_empty() {
+        eprintln!("Session state is already in sync.");
+    }
+    Ok(())
+}
+
+/// The managed worktree `path` is in, comparing canonical paths since
+/// tmux reports resolved directories.
+fn worktree_containing<'a>(worktrees: &'a [WorktreeInfo], path: &Path) -> Option<&'a WorktreeInfo> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    worktrees.iter().find(|wt| {
+        let root = wt.path.canonicalize().unwrap_or_else(|_| wt.path.clone());
+        path.starts_with(root)
+    })
 }
 
 fn cmd_session_ls(tmux: &TmuxManager) -> Result<()> {
@@ -223,7 +690,7 @@ fn cmd_session_ls(tmux: &TmuxManager) -> Result<()> {
     }
 
     for window in &windows {
-        if window.name == "status" {
+        if window.name == STATUS_WINDOW {
             continue;
         }
 
@@ -237,29 +704,136 @@ fn cmd_session_ls(tmux: &TmuxManager) -> Result<()> {
     Ok(())
 }
 
+/// `wt session add <name>...`: add each worktree, then switch to the last
+/// one. With several names, a failure is reported and the rest are still
+/// added, but nothing is attached so the errors stay visible.
+fn cmd_session_add(
+    context: &SessionCmdContext<'_>,
+    names: &[String],
+    options: &SessionAddOptions,
+) -> Result<()> {
+    if let [name] = names {
+        add_session_worktree(context, name, options)?;
+        return attach_added(context, name, options);
+    }
+
+    let mut last_added = None;
+    let mut failed = 0;
+    for name in names {
+        match add_session_worktree(context, name, options) {
+            Ok(()) => last_added = Some(name),
+            Err(error) => {
+                eprintln!("wt: could not add '{}': {:#}", name, error);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} worktrees could not be added; run 'wt session' to attach to the rest",
+            failed,
+            names.len()
+        );
+    }
+    match last_added {
+        Some(name) => attach_added(context, name, options),
+        None => Ok(()),
+    }
+}
+
+fn add_session_worktree(
+    context: &SessionCmdContext<'_>,
+    name: &str,
+    options: &SessionAddOptions,
+) -> Result<()> {
+    match context.mode {
+        SessionMode::Panes if options.attach_here => cmd_session_add_here(context, name, options),
+        SessionMode::Panes => cmd_session_add_panes(context, name, options),
+        SessionMode::Windows => cmd_session_add_windows(context, name, options),
+    }
+}
+
+/// Show the worktree added last: select its window from inside the
+/// session, otherwise attach. Skipped for `--no-attach` and for
+/// `--attach-here`, whose split is already on screen.
+fn attach_added(
+    context: &SessionCmdContext<'_>,
+    name: &str,
+    options: &SessionAddOptions,
+) -> Result<()> {
+    if options.attach_here {
+        return Ok(());
+    }
+    if options.no_attach {
+        print_attach_hint(context);
+        return Ok(());
+    }
+
+    match context.mode {
+        SessionMode::Panes => {
+            let tmux = panes_tmux(context);
+            if tmux.is_inside_session() {
+                tmux.select_window(name)?;
+            } else {
+                eprintln!("Entering session...");
+                tmux.enter()?;
+            }
+        }
+        SessionMode::Windows => {
+            TmuxManager::new(&context.config.session.session_name_for(name)).enter()?;
+        }
+    }
+    Ok(())
+}
+
+/// Tell a `--no-attach` caller how to get to the session later, unless
+/// they are already in it.
+fn print_attach_hint(context: &SessionCmdContext<'_>) {
+    if context.mode == SessionMode::Panes && panes_tmux(context).is_inside_session() {
+        return;
+    }
+
+    let mode_flag = if context.mode == context.config.session.mode {
+        ""
+    } else {
+        match context.mode {
+            SessionMode::Panes => " --mode panes",
+            SessionMode::Windows => " --mode windows",
+        }
+    };
+    eprintln!(
+        "Not attaching; run 'wt session{}' to attach later.",
+        mode_flag
+    );
+}
+
 fn cmd_session_add_panes(
     context: &SessionCmdContext<'_>,
     name: &str,
-    base: &str,
-    panes_override: Option<u8>,
-    watch: bool,
+    options: &SessionAddOptions,
 ) -> Result<()> {
-    let tmux = panes_tmux();
-    let worktree_path = ensure_worktree_path(context, name, base)?;
-    let panes = context.effective_panes(panes_override);
-    let inside_session = tmux.is_inside_session();
+    let tmux = panes_tmux(context);
+    let worktree_path = ensure_worktree_path(context, name, options.base(context))?;
+    let panes = context.effective_panes(options.panes);
 
     if !tmux.session_exists()? {
         eprintln!("Creating tmux session: {}", SESSION_NAME);
-        if watch {
+        if options.watch {
             create_status_window_session(&tmux, &context.repo.root)?;
             tmux.create_window(name, &worktree_path)?;
         } else {
             tmux.create_session(name, &worktree_path)?;
         }
-        tmux.setup_worktree_layout(name, &worktree_path, panes, &context.config.session)?;
+        tmux.setup_worktree_layout(
+            name,
+            &worktree_path,
+            panes,
+            options.layout(context),
+            &context.config.session,
+        )?;
     } else {
-        if watch {
+        if options.watch {
             ensure_status_window(&tmux, &context.repo.root)?;
         }
 
@@ -267,33 +841,88 @@ fn cmd_session_add_panes(
 
         if windows.iter().any(|window| window.name == name) {
             eprintln!("Window '{}' already exists in session.", name);
-            if inside_session {
-                tmux.select_window(name)?;
-            }
         } else {
             eprintln!("Adding window: {} ({} panes)", name, panes);
             tmux.create_window(name, &worktree_path)?;
-            tmux.setup_worktree_layout(name, &worktree_path, panes, &context.config.session)?;
+            tmux.setup_worktree_layout(
+                name,
+                &worktree_path,
+                panes,
+                options.layout(context),
+                &context.config.session,
+            )?;
         }
     }
 
-    let mut state = SessionState::load()?.unwrap_or_else(|| SessionState::new(SESSION_NAME));
+    if let Some(prompt) = &options.prompt {
+        inject_prompt(
+            context,
+            &tmux,
+            name,
+            0,
+            prompt,
+            options.wait_ready.as_deref(),
+        )?;
+    }
+
+    let mut state =
+        SessionState::load(&context.repo.root)?.unwrap_or_else(|| SessionState::new(SESSION_NAME));
     state.add_worktree(name, 0, panes, worktree_path);
+    state.set_agent_cmd(name, options.agent.clone());
     state.sync_with_tmux(&tmux)?;
-    state.save()?;
+    state.save(&context.repo.root)?;
+    Ok(())
+}
 
-    if inside_session {
-        tmux.select_window(name)?;
-    } else {
-        eprintln!("Entering session...");
-        tmux.enter()?;
+/// `session add --attach-here`: open the worktree's agent in a new split
+/// of the current window rather than in a window of its own.
+fn cmd_session_add_here(
+    context: &SessionCmdContext<'_>,
+    name: &str,
+    options: &SessionAddOptions,
+) -> Result<()> {
+    let tmux = panes_tmux(context);
+    if !tmux.is_inside_session() {
+        anyhow::bail!(
+            "--attach-here only works from inside the '{}' session",
+            SESSION_NAME
+        );
+    }
+    let Some(host) = tmux
+        .list_windows()?
+        .into_iter()
+        .find(|window| window.active)
+    else {
+        anyhow::bail!("Could not find the current window in '{}'", SESSION_NAME);
+    };
+
+    let worktree_path = ensure_worktree_path(context, name, options.base(context))?;
+    eprintln!("Splitting window '{}' for: {}", host.name, name);
+    let pane = tmux.split_window_horizontal(&host.name, &worktree_path)?;
+    tmux.send_keys(&host.name, pane, &context.config.session.agent_cmd)?;
+
+    if let Some(prompt) = &options.prompt {
+        inject_prompt(
+            context,
+            &tmux,
+            &host.name,
+            pane,
+            prompt,
+            options.wait_ready.as_deref(),
+        )?;
     }
 
+    let mut state =
+        SessionState::load(&context.repo.root)?.unwrap_or_else(|| SessionState::new(SESSION_NAME));
+    state.add_split_worktree(name, &host.name, host.index, worktree_path);
+    state.set_agent_cmd(name, options.agent.clone());
+    state.sync_with_tmux(&tmux)?;
+    state.save(&context.repo.root)?;
     Ok(())
 }
 
 fn cmd_session_rm_panes(context: &SessionCmdContext<'_>, name: &str) -> Result<()> {
-    let tmux = panes_tmux();
+    let tmux = panes_tmux(context);
 
     if !tmux.session_exists()? {
         eprintln!("No session found.");
@@ -314,21 +943,127 @@ fn cmd_session_rm_panes(context: &SessionCmdContext<'_>, name: &str) -> Result<(
     let remaining: Vec<_> = tmux
         .list_windows()?
         .into_iter()
-        .filter(|window| window.name != "status")
+        .filter(|window| window.name != STATUS_WINDOW)
         .collect();
     let session_drained = remaining.is_empty();
     if session_drained {
         eprintln!("Session is empty.");
     }
 
-    if let Some(mut state) = SessionState::load()? {
+    if let Some(mut state) = SessionState::load(&context.repo.root)? {
         if session_drained {
             state.clear_panes_state();
         } else {
             state.remove_worktree(name);
             state.sync_with_tmux(&tmux)?;
         }
-        save_state_or_clear_if_empty(&state)?;
+        save_state_or_clear_if_empty(&context.repo.root, &state)?;
+    }
+
+    Ok(())
+}
+
+fn cmd_session_rename(repo_root: &Path, tmux: &TmuxManager, old: &str, new: &str) -> Result<()> {
+    if !tmux.session_exists()? {
+        anyhow::bail!("No session found.");
+    }
+
+    let windows = tmux.list_windows()?;
+    if !windows.iter().any(|window| window.name == old) {
+        anyhow::bail!("Window '{}' not found in session.", old);
+    }
+    if new == STATUS_WINDOW || windows.iter().any(|window| window.name == new) {
+        anyhow::bail!("A window named '{}' already exists in the session.", new);
+    }
+
+    tmux.rename_window(old, new)?;
+    eprintln!("Renamed window: {} -> {}", old, new);
+
+    if let Some(mut state) = SessionState::load(repo_root)? {
+        if state.rename_worktree(old, new) {
+            state.save(repo_root)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_session_restart(
+    context: &SessionCmdContext<'_>,
+    tmux: &TmuxManager,
+    name: &str,
+    prompt: Option<&str>,
+    wait_ready: Option<&str>,
+) -> Result<()> {
+    if !tmux.session_exists()? {
+        anyhow::bail!("No session found.");
+    }
+    if name == STATUS_WINDOW
+        || !tmux
+            .list_windows()?
+            .iter()
+            .any(|window| window.name == name)
+    {
+        anyhow::bail!("Window '{}' not found in session.", name);
+    }
+
+    tmux.send_interrupt(name, 0)?;
+    tmux.send_keys(name, 0, &context.config.session.agent_cmd)?;
+    eprintln!("Restarted agent in {}", name);
+
+    if let Some(prompt) = prompt {
+        inject_prompt(context, tmux, name, 0, prompt, wait_ready)?;
+    }
+
+    Ok(())
+}
+
+fn cmd_session_broadcast(
+    tmux: &TmuxManager,
+    pane: u32,
+    confirm: bool,
+    command: &str,
+) -> Result<()> {
+    if !tmux.session_exists()? {
+        eprintln!("No session found.");
+        return Ok(());
+    }
+
+    let windows: Vec<_> = tmux
+        .list_windows()?
+        .into_iter()
+        .filter(|window| window.name != STATUS_WINDOW)
+        .collect();
+    if windows.is_empty() {
+        eprintln!("No worktrees in session.");
+        return Ok(());
+    }
+
+    if confirm {
+        if !std::io::stderr().is_terminal() {
+            anyhow::bail!("Cannot confirm broadcast without a terminal.");
+        }
+        let confirmed = Confirm::new()
+            .with_prompt(format!(
+                "Send '{}' to pane {} of {} window(s)?",
+                command,
+                pane,
+                windows.len()
+            ))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            return Ok(());
+        }
+    }
+
+    for window in &windows {
+        if pane >= window.pane_count {
+            eprintln!("Skipping {}: no pane {}", window.name, pane);
+            continue;
+        }
+        tmux.send_keys(&window.name, pane, command)?;
+        eprintln!("Sent to {}", window.name);
     }
 
     Ok(())
@@ -337,16 +1072,17 @@ fn cmd_session_rm_panes(context: &SessionCmdContext<'_>, name: &str) -> Result<(
 fn cmd_session_add_windows(
     context: &SessionCmdContext<'_>,
     name: &str,
-    base: &str,
-    panes_override: Option<u8>,
-    watch: bool,
+    options: &SessionAddOptions,
 ) -> Result<()> {
-    if watch {
+    if options.watch {
         eprintln!("Note: --watch is ignored in windows mode.");
     }
+    if options.attach_here {
+        eprintln!("Note: --attach-here is ignored in windows mode.");
+    }
 
-    let worktree_path = ensure_worktree_path(context, name, base)?;
-    let panes = context.effective_panes(panes_override);
+    let worktree_path = ensure_worktree_path(context, name, options.base(context))?;
+    let panes = context.effective_panes(options.panes);
     let session_name = context.config.session.session_name_for(name);
     let tmux = TmuxManager::new(&session_name);
 
@@ -361,12 +1097,56 @@ fn cmd_session_add_windows(
         tmux.setup_worktree_windows(&worktree_path, panes, &context.config.session)?;
     }
 
-    persist_windows_session(name, &session_name, &worktree_path, panes)?;
-    tmux.enter()
+    if let Some(prompt) = &options.prompt {
+        inject_prompt(
+            context,
+            &tmux,
+            "agent",
+            0,
+            prompt,
+            options.wait_ready.as_deref(),
+        )?;
+    }
+
+    persist_windows_session(
+        &context.repo.root,
+        name,
+        &session_name,
+        &worktree_path,
+        panes,
+    )?;
+    Ok(())
+}
+
+/// Send `prompt` to the agent in `window`.`pane`, first waiting for the agent to print
+/// its readiness pattern (from `--wait-ready` or `ready_patterns` config)
+/// so keystrokes are not lost while it starts up.
+fn inject_prompt(
+    context: &SessionCmdContext<'_>,
+    tmux: &TmuxManager,
+    window: &str,
+    pane: u32,
+    prompt: &str,
+    wait_ready: Option<&str>,
+) -> Result<()> {
+    let session_config = &context.config.session;
+    if let Some(pattern) = wait_ready.or_else(|| session_config.ready_pattern()) {
+        let timeout = Duration::from_secs(session_config.ready_timeout_secs);
+        eprintln!("Waiting for agent to be ready...");
+        if !tmux.wait_for_pane_ready(window, pane, pattern, timeout)? {
+            eprintln!(
+                "Agent did not show '{}' within {}s; sending prompt anyway.",
+                pattern, session_config.ready_timeout_secs
+            );
+        }
+    }
+
+    tmux.send_keys(window, pane, prompt)?;
+    Ok(())
 }
 
-fn cmd_session_attach_windows() -> Result<()> {
-    let Some(state) = load_windows_state_or_report_empty()? else {
+fn cmd_session_attach_windows(repo_root: &Path) -> Result<()> {
+    let Some(state) = load_windows_state_or_report_empty(repo_root)? else {
         return Ok(());
     };
 
@@ -390,16 +1170,17 @@ fn cmd_session_attach_windows() -> Result<()> {
         return Ok(());
     }
 
-    TmuxManager::new(&items[selection]).enter()
+    TmuxManager::new(&items[selection]).enter()?;
+    Ok(())
 }
 
-fn cmd_session_ls_windows() -> Result<()> {
-    let Some(state) = load_windows_state_or_report_empty()? else {
+fn cmd_session_ls_windows(repo_root: &Path, config: &SessionConfig) -> Result<()> {
+    let Some(state) = load_windows_state_or_report_empty(repo_root)? else {
         return Ok(());
     };
 
     for (_, info) in sorted_windows_sessions(&state) {
-        let tmux = TmuxManager::new(&info.session_name);
+        let tmux = TmuxManager::new(&info.session_name).with_agent_program(config.agent_program());
         let attached = tmux.is_attached().unwrap_or(false);
         let agent_status = agent_window_status(&tmux);
         let marker = if attached { "*" } else { " " };
@@ -411,7 +1192,7 @@ fn cmd_session_ls_windows() -> Result<()> {
 
 fn cmd_session_rm_windows(context: &SessionCmdContext<'_>, name: &str) -> Result<()> {
     let probe = probe_session_rm(context, name)?;
-    let mut state = SessionState::load()?;
+    let mut state = SessionState::load(&context.repo.root)?;
 
     let session_name = state
         .as_ref()
@@ -430,7 +1211,7 @@ fn cmd_session_rm_windows(context: &SessionCmdContext<'_>, name: &str) -> Result
     if let Some(loaded) = state.as_mut() {
         let removed = loaded.remove_windows_session(name).is_some();
         prune_windows_state(loaded);
-        save_state_or_clear_if_empty(loaded)?;
+        save_state_or_clear_if_empty(&context.repo.root, loaded)?;
         if removed && !session_existed {
             eprintln!(
                 "Removed stale windows-mode entry for '{}' (session '{}').",
@@ -458,7 +1239,15 @@ fn cmd_session_rm_windows(context: &SessionCmdContext<'_>, name: &str) -> Result
     Ok(())
 }
 
-fn cmd_session_watch(tmux: &TmuxManager, interval: u64) -> Result<()> {
+fn cmd_session_watch(
+    tmux: &TmuxManager,
+    interval: u64,
+    sort: WatchSort,
+    only: &[AgentStatus],
+    notify: bool,
+    json: bool,
+    config: &SessionConfig,
+) -> Result<()> {
     use std::io::Write;
 
     if !tmux.session_exists()? {
@@ -467,50 +1256,187 @@ fn cmd_session_watch(tmux: &TmuxManager, interval: u64) -> Result<()> {
     }
 
     let interval_duration = std::time::Duration::from_secs(interval);
+    let mut previous: HashMap<String, AgentStatus> = HashMap::new();
 
     loop {
+        let windows = tmux.list_windows()?;
+        let worktrees = watch_rows(&windows, sort, only);
+
+        if json {
+            // A reader like `head -1` closes the pipe once it has enough;
+            // that ends the watch rather than being an error.
+            let mut stdout = std::io::stdout().lock();
+            match writeln!(stdout, "{}", watch_snapshot(&worktrees)).and_then(|_| stdout.flush()) {
+                Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+                result => result?,
+            }
+            drop(stdout);
+            std::thread::sleep(interval_duration);
+            continue;
+        }
+
         print!("\x1B[2J\x1B[H");
         std::io::stdout().flush()?;
 
         println!("wt session status (refresh: {}s)\n", interval);
 
-        let windows = tmux.list_windows()?;
-        let worktrees: Vec<_> = windows
+        if notify {
+            for name in went_idle(&previous, &windows) {
+                notify_idle(name, config.notify_cmd.as_deref());
+            }
+        }
+        previous = windows
             .iter()
-            .filter(|window| window.name != "status")
+            .map(|window| (window.name.clone(), window.agent_status))
             .collect();
 
         if worktrees.is_empty() {
             println!("  No worktrees in session.");
         } else {
+            let now = unix_now();
             for window in &worktrees {
                 let status_icon = match window.agent_status {
                     AgentStatus::Active => "\x1B[32m●\x1B[0m",
                     AgentStatus::Idle => "\x1B[90m○\x1B[0m",
+                    AgentStatus::Stopped => "\x1B[1;31m✗\x1B[0m",
                     AgentStatus::Unknown => "\x1B[33m?\x1B[0m",
                 };
                 let active_marker = if window.active { " ←" } else { "" };
+                let quiet_for = window
+                    .last_activity
+                    .map(|activity| now.saturating_sub(activity));
+                let quiet = match quiet_for {
+                    Some(secs)
+                        if is_stalled(window.agent_status, secs, config.stall_after_secs) =>
+                    {
+                        format!(" \x1B[31midle {} (stalled?)\x1B[0m", format_duration(secs))
+                    }
+                    Some(secs) => format!(" \x1B[90midle {}\x1B[0m", format_duration(secs)),
+                    None => String::new(),
+                };
                 println!(
-                    "  {} [{}] {}{} ({} panes)",
-                    status_icon, window.index, window.name, active_marker, window.pane_count
+                    "  {} [{}] {}{} ({} panes){}",
+                    status_icon, window.index, window.name, active_marker, window.pane_count, quiet
                 );
             }
         }
 
-        println!("\n\x1B[90m● active  ○ idle  ? unknown\x1B[0m");
+        println!("\n\x1B[90m● active  ○ idle  ✗ stopped  ? unknown\x1B[0m");
         println!("\x1B[90mPress Ctrl+C to exit\x1B[0m");
 
         std::thread::sleep(interval_duration);
     }
 }
 
+/// One `wt session watch --json` line: the shown windows as an array.
+fn watch_snapshot(rows: &[&TmuxWindow]) -> String {
+    let rows: Vec<_> = rows
+        .iter()
+        .map(|window| {
+            serde_json::json!({
+                "name": window.name,
+                "index": window.index,
+                "status": window
+                    .agent_status
+                    .to_possible_value()
+                    .map(|value| value.get_name().to_string()),
+                "panes": window.pane_count,
+                "active": window.active,
+            })
+        })
+        .collect();
+    serde_json::Value::Array(rows).to_string()
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Whether an agent is still running but its window has been silent for
+/// at least `stall_after` seconds. Idle agents are finished, not stalled.
+fn is_stalled(status: AgentStatus, quiet_secs: u64, stall_after: u64) -> bool {
+    status == AgentStatus::Active && quiet_secs >= stall_after
+}
+
+/// `42s`, `7m` or `3h`.
+fn format_duration(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3600),
+    }
+}
+
+/// Names of windows whose agent was active on the previous refresh and is
+/// idle now.
+fn went_idle<'a>(
+    previous: &HashMap<String, AgentStatus>,
+    windows: &'a [TmuxWindow],
+) -> Vec<&'a str> {
+    windows
+        .iter()
+        .filter(|window| window.agent_status == AgentStatus::Idle)
+        .filter(|window| previous.get(&window.name) == Some(&AgentStatus::Active))
+        .map(|window| window.name.as_str())
+        .collect()
+}
+
+/// Best effort: a failing `notify_cmd` must not stop the watch loop.
+fn notify_idle(window: &str, notify_cmd: Option<&str>) {
+    use std::io::Write;
+
+    print!("\x07");
+    let _ = std::io::stdout().flush();
+
+    if let Some(notify_cmd) = notify_cmd {
+        let _ = std::process::Command::new("sh")
+            .args(["-c", notify_cmd])
+            .env("WT_WINDOW", window)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .run_status();
+    }
+}
+
+/// The worktree windows `wt session watch` shows, filtered to `only`
+/// (all when empty) and ordered by `sort`.
+fn watch_rows<'a>(
+    windows: &'a [TmuxWindow],
+    sort: WatchSort,
+    only: &[AgentStatus],
+) -> Vec<&'a TmuxWindow> {
+    let mut rows: Vec<_> = windows
+        .iter()
+        .filter(|window| window.name != STATUS_WINDOW)
+        .filter(|window| only.is_empty() || only.contains(&window.agent_status))
+        .collect();
+    if sort == WatchSort::Status {
+        // Stable, so index order is kept within each status
+        rows.sort_by_key(|window| status_rank(window.agent_status));
+    }
+    rows
+}
+
+fn status_rank(status: AgentStatus) -> u8 {
+    match status {
+        AgentStatus::Stopped => 0,
+        AgentStatus::Active => 1,
+        AgentStatus::Idle => 2,
+        AgentStatus::Unknown => 3,
+    }
+}
+
 fn persist_windows_session(
+    repo_root: &Path,
     worktree_name: &str,
     session_name: &str,
     worktree_path: &Path,
     panes: u8,
 ) -> Result<()> {
-    let mut state = SessionState::load()?.unwrap_or_else(|| SessionState::new(SESSION_NAME));
+    let mut state =
+        SessionState::load(repo_root)?.unwrap_or_else(|| SessionState::new(SESSION_NAME));
 
     state.add_windows_session(
         worktree_name,
@@ -521,21 +1447,21 @@ fn persist_windows_session(
         },
     );
     prune_windows_state(&mut state);
-    state.save()
+    state.save(repo_root)
 }
 
-fn load_windows_state() -> Result<Option<SessionState>> {
-    let Some(mut state) = SessionState::load()? else {
+fn load_windows_state(repo_root: &Path) -> Result<Option<SessionState>> {
+    let Some(mut state) = SessionState::load(repo_root)? else {
         return Ok(None);
     };
 
     prune_windows_state(&mut state);
-    save_state_or_clear_if_empty(&state)?;
+    save_state_or_clear_if_empty(repo_root, &state)?;
     Ok(Some(state))
 }
 
-fn load_windows_state_or_report_empty() -> Result<Option<SessionState>> {
-    let Some(state) = load_windows_state()? else {
+fn load_windows_state_or_report_empty(repo_root: &Path) -> Result<Option<SessionState>> {
+    let Some(state) = load_windows_state(repo_root)? else {
         eprintln!("{}", NO_WINDOWS_SESSIONS_MSG);
         return Ok(None);
     };
@@ -554,11 +1480,11 @@ fn prune_windows_state(state: &mut SessionState) {
     }
 }
 
-fn save_state_or_clear_if_empty(state: &SessionState) -> Result<()> {
+fn save_state_or_clear_if_empty(repo_root: &Path, state: &SessionState) -> Result<()> {
     if state.is_empty() {
-        SessionState::clear()
+        SessionState::clear(repo_root)
     } else {
-        state.save()
+        state.save(repo_root)
     }
 }
 
@@ -569,11 +1495,12 @@ fn sorted_windows_sessions(state: &SessionState) -> Vec<(&String, &WindowsSessio
 }
 
 fn windows_layout_names(panes: u8) -> Vec<String> {
-    if panes == 3 {
-        vec!["agent".to_string(), "shell".to_string(), "edit".to_string()]
-    } else {
-        vec!["agent".to_string(), "shell".to_string()]
-    }
+    let names: &[&str] = match panes {
+        1 => &["agent"],
+        3 => &["agent", "shell", "edit"],
+        _ => &["agent", "shell"],
+    };
+    names.iter().map(|name| name.to_string()).collect()
 }
 
 fn agent_window_status(tmux: &TmuxManager) -> AgentStatus {
@@ -596,7 +1523,7 @@ fn probe_session_rm(context: &SessionCmdContext<'_>, name: &str) -> Result<Sessi
         false
     };
 
-    let state = SessionState::load()?;
+    let state = SessionState::load(&context.repo.root)?;
     let tracked_windows_session_name = state
         .as_ref()
         .and_then(|loaded| loaded.windows_sessions.get(name))
@@ -742,8 +1669,28 @@ mod tests {
         assert_eq!(windows_rm_hint("demo", &probe()), None);
     }
 
+    #[test]
+    fn test_worktree_containing_matches_subdirectories() {
+        let dir = tempfile::tempdir().unwrap();
+        let worktree = |name: &str| WorktreeInfo {
+            task_id: name.to_string(),
+            path: dir.path().join(".worktrees").join(name),
+            branch: name.to_string(),
+            dirty: None,
+            ahead_behind: None,
+        };
+        let worktrees = [worktree("api"), worktree("web")];
+        let src = dir.path().join(".worktrees/web/src");
+        std::fs::create_dir_all(&src).unwrap();
+
+        let found = worktree_containing(&worktrees, &src).unwrap();
+        assert_eq!(found.task_id, "web");
+        assert!(worktree_containing(&worktrees, dir.path()).is_none());
+    }
+
     #[test]
     fn test_windows_layout_names_match_pane_count() {
+        assert_eq!(windows_layout_names(1), vec!["agent".to_string()]);
         assert_eq!(
             windows_layout_names(2),
             vec!["agent".to_string(), "shell".to_string()]
@@ -753,4 +1700,103 @@ mod tests {
             vec!["agent".to_string(), "shell".to_string(), "edit".to_string()]
         );
     }
+
+    fn window(index: u32, name: &str, agent_status: AgentStatus) -> TmuxWindow {
+        TmuxWindow {
+            index,
+            name: name.to_string(),
+            pane_count: 2,
+            active: false,
+            agent_status,
+            last_activity: None,
+        }
+    }
+
+    fn row_names(rows: &[&TmuxWindow]) -> Vec<String> {
+        rows.iter().map(|window| window.name.clone()).collect()
+    }
+
+    #[test]
+    fn test_watch_rows_default_keeps_index_order() {
+        let windows = vec![
+            window(0, STATUS_WINDOW, AgentStatus::Idle),
+            window(1, "a", AgentStatus::Idle),
+            window(2, "b", AgentStatus::Active),
+            window(3, "c", AgentStatus::Unknown),
+            window(4, "status", AgentStatus::Idle),
+        ];
+        let rows = watch_rows(&windows, WatchSort::Index, &[]);
+        assert_eq!(row_names(&rows), vec!["a", "b", "c", "status"]);
+    }
+
+    #[test]
+    fn test_watch_rows_sorts_active_first_and_filters() {
+        let windows = vec![
+            window(1, "a", AgentStatus::Unknown),
+            window(2, "b", AgentStatus::Idle),
+            window(3, "c", AgentStatus::Active),
+            window(4, "d", AgentStatus::Active),
+        ];
+        let rows = watch_rows(&windows, WatchSort::Status, &[]);
+        assert_eq!(row_names(&rows), vec!["c", "d", "b", "a"]);
+
+        let rows = watch_rows(&windows, WatchSort::Index, &[AgentStatus::Active]);
+        assert_eq!(row_names(&rows), vec!["c", "d"]);
+    }
+
+    #[test]
+    fn test_watch_snapshot_is_one_json_line() {
+        let windows = [
+            window(1, "a", AgentStatus::Active),
+            window(2, "b", AgentStatus::Unknown),
+        ];
+        let rows: Vec<_> = windows.iter().collect();
+
+        let line = watch_snapshot(&rows);
+        assert!(!line.contains('\n'));
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::json!([
+                {"name": "a", "index": 1, "status": "active", "panes": 2, "active": false},
+                {"name": "b", "index": 2, "status": "unknown", "panes": 2, "active": false},
+            ])
+        );
+        assert_eq!(watch_snapshot(&[]), "[]");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(59), "59s");
+        assert_eq!(format_duration(60), "1m");
+        assert_eq!(format_duration(3599), "59m");
+        assert_eq!(format_duration(7200), "2h");
+    }
+
+    #[test]
+    fn test_is_stalled_only_flags_quiet_running_agents() {
+        assert!(is_stalled(AgentStatus::Active, 300, 300));
+        assert!(!is_stalled(AgentStatus::Active, 299, 300));
+        assert!(!is_stalled(AgentStatus::Idle, 3600, 300));
+        assert!(!is_stalled(AgentStatus::Unknown, 3600, 300));
+    }
+
+    #[test]
+    fn test_went_idle_only_reports_active_to_idle() {
+        let previous = HashMap::from([
+            ("a".to_string(), AgentStatus::Active),
+            ("b".to_string(), AgentStatus::Idle),
+            ("c".to_string(), AgentStatus::Active),
+            ("d".to_string(), AgentStatus::Unknown),
+        ]);
+        let windows = vec![
+            window(1, "a", AgentStatus::Idle),
+            window(2, "b", AgentStatus::Idle),
+            window(3, "c", AgentStatus::Active),
+            window(4, "d", AgentStatus::Idle),
+            window(5, "new", AgentStatus::Idle),
+        ];
+        assert_eq!(went_idle(&previous, &windows), vec!["a"]);
+    }
 }
@@ -1,8 +1,19 @@
 use anyhow::{Context, Result};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-
-pub fn spawn_wt_shell(wt_path: &Path, wt_name: &str, branch: &str) -> Result<()> {
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::command::CommandExt;
+use crate::config::ShellConfig;
+
+pub fn spawn_wt_shell(
+    wt_path: &Path,
+    wt_name: &str,
+    branch: &str,
+    config: &ShellConfig,
+) -> Result<()> {
     if std::env::var("WT_ACTIVE").is_ok() {
         anyhow::bail!("Already in a wt shell. Use 'wt ls' to switch or 'exit' first.");
     }
@@ -15,11 +26,22 @@ pub fn spawn_wt_shell(wt_path: &Path, wt_name: &str, branch: &str) -> Result<()>
 
     eprintln!("Entering worktree: {}", wt_name);
 
+    let mut cmd = shell_cmd(&shell_path, wt_path, wt_name, branch);
+    // Each shell's startup hook reads the rendered prefix from the
+    // environment, which avoids quoting it into generated shell code.
+    cmd.env(
+        "_WT_PROMPT_PREFIX",
+        config.render_prompt(wt_name, branch, wt_path),
+    );
+    if let Some(init) = &config.init {
+        cmd.env("_WT_SHELL_INIT", init);
+    }
+
     match shell_name {
-        "bash" => spawn_bash(&shell_path, wt_path, wt_name, branch)?,
-        "zsh" => spawn_zsh(&shell_path, wt_path, wt_name, branch)?,
-        "fish" => spawn_fish(&shell_path, wt_path, wt_name, branch)?,
-        _ => spawn_shell(shell_cmd(&shell_path, wt_path, wt_name, branch))?,
+        "bash" => spawn_bash(cmd)?,
+        "zsh" => spawn_zsh(cmd)?,
+        "fish" => spawn_fish(cmd)?,
+        _ => spawn_shell(cmd)?,
     };
 
     show_exit_status(wt_path)?;
@@ -38,51 +60,120 @@ fn shell_cmd(shell_path: &str, wt_path: &Path, wt_name: &str, branch: &str) -> C
     cmd
 }
 
+/// A temporary rc file or directory that is removed when dropped, so it is
+/// cleaned up on early returns as well as after the shell exits.
+struct TempRc {
+    path: PathBuf,
+}
+
+impl TempRc {
+    /// Create a new file with `contents` under a unique temp path.
+    fn file(prefix: &str, contents: &str) -> Result<Self> {
+        let path = unique_temp_path(prefix);
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let temp_rc = Self { path };
+        file.write_all(contents.as_bytes())?;
+        Ok(temp_rc)
+    }
+
+    /// Create a new, empty directory under a unique temp path.
+    fn dir(prefix: &str) -> Result<Self> {
+        let path = unique_temp_path(prefix);
+        std::fs::create_dir(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self { path })
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempRc {
+    fn drop(&mut self) {
+        let _ = if self.path.is_dir() {
+            std::fs::remove_dir_all(&self.path)
+        } else {
+            std::fs::remove_file(&self.path)
+        };
+    }
+}
+
+/// A temp path that is unique across processes and across repeated calls
+/// within one process.
+fn unique_temp_path(prefix: &str) -> PathBuf {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or(0);
+    let sequence = COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "{}-{}-{:08x}-{}",
+        prefix,
+        std::process::id(),
+        nanos,
+        sequence
+    ))
+}
+
 fn spawn_shell(mut cmd: Command) -> Result<()> {
-    cmd.status()?;
+    cmd.run_status()?;
     Ok(())
 }
 
-fn spawn_bash(shell_path: &str, wt_path: &Path, wt_name: &str, branch: &str) -> Result<()> {
-    let rcfile_content = "[ -f ~/.bashrc ] && source ~/.bashrc; PS1=\"(wt) $PS1\"".to_string();
-    let temp_rc = std::env::temp_dir().join(format!("wt-bashrc-{}", std::process::id()));
-    std::fs::write(&temp_rc, &rcfile_content)?;
+/// Loads the user's `~/.bashrc` (if any) first, then re-applies the prompt
+/// prefix before every prompt, since themes that rebuild `PS1` from
+/// `PROMPT_COMMAND` would otherwise drop it.
+const BASH_RCFILE: &str = r#"if [ -f ~/.bashrc ]; then
+    source ~/.bashrc
+fi
+if [ -n "$_WT_SHELL_INIT" ]; then
+    eval "$_WT_SHELL_INIT"
+fi
+_wt_apply_prompt_prefix() {
+    [[ -z $_WT_PROMPT_PREFIX || $PS1 == "$_WT_PROMPT_PREFIX"* ]] || PS1="${_WT_PROMPT_PREFIX}$PS1"
+}
+PROMPT_COMMAND="${PROMPT_COMMAND:+$PROMPT_COMMAND;}_wt_apply_prompt_prefix"
+_wt_apply_prompt_prefix
+"#;
 
-    let mut cmd = shell_cmd(shell_path, wt_path, wt_name, branch);
-    cmd.arg("--rcfile").arg(&temp_rc);
-    spawn_shell(cmd)?;
+/// fish runs `--init-command` after its config, so the user's prompt is
+/// already defined when it is wrapped.
+const FISH_INIT_COMMAND: &str = "functions -c fish_prompt _wt_orig_prompt 2>/dev/null; \
+     function fish_prompt; echo -n \"$_WT_PROMPT_PREFIX\"; \
+     functions -q _wt_orig_prompt; and _wt_orig_prompt; end; \
+     set -q _WT_SHELL_INIT; and eval $_WT_SHELL_INIT";
 
-    let _ = std::fs::remove_file(&temp_rc);
-    Ok(())
+fn spawn_bash(mut cmd: Command) -> Result<()> {
+    let temp_rc = TempRc::file("wt-bashrc", BASH_RCFILE)?;
+
+    cmd.arg("--rcfile").arg(temp_rc.path());
+    spawn_shell(cmd)
 }
 
-fn spawn_zsh(shell_path: &str, wt_path: &Path, wt_name: &str, branch: &str) -> Result<()> {
+fn spawn_zsh(mut cmd: Command) -> Result<()> {
     let temp_dir = create_zsh_wrapper()?;
 
-    let mut cmd = shell_cmd(shell_path, wt_path, wt_name, branch);
-    cmd.env("ZDOTDIR", &temp_dir).env(
+    cmd.env("ZDOTDIR", temp_dir.path()).env(
         "_WT_ORIG_ZDOTDIR",
         std::env::var("ZDOTDIR").unwrap_or_else(|_| std::env::var("HOME").unwrap_or_default()),
     );
-    spawn_shell(cmd)?;
-
-    let _ = std::fs::remove_dir_all(&temp_dir);
-    Ok(())
+    spawn_shell(cmd)
 }
 
-fn spawn_fish(shell_path: &str, wt_path: &Path, wt_name: &str, branch: &str) -> Result<()> {
-    let mut cmd = shell_cmd(shell_path, wt_path, wt_name, branch);
-    cmd.arg("--init-command").arg(
-        "functions -c fish_prompt _wt_orig_prompt 2>/dev/null; \
-             function fish_prompt; echo -n '(wt) '; _wt_orig_prompt; end",
-    );
+fn spawn_fish(mut cmd: Command) -> Result<()> {
+    cmd.arg("--init-command").arg(FISH_INIT_COMMAND);
     spawn_shell(cmd)
 }
 
-fn create_zsh_wrapper() -> Result<PathBuf> {
-    let temp_dir = std::env::temp_dir().join(format!("wt-zsh-{}", std::process::id()));
-    std::fs::create_dir_all(&temp_dir)?;
-    let functions_dir = temp_dir.join("functions");
+fn create_zsh_wrapper() -> Result<TempRc> {
+    let temp_dir = TempRc::dir("wt-zsh")?;
+    let functions_dir = temp_dir.path().join("functions");
     std::fs::create_dir_all(&functions_dir)?;
 
     // zsh reads `.zshenv` before `.zshrc`, so this is the earliest safe place
@@ -110,7 +201,8 @@ function _wt_replay_compdef {
 }
 
 function _wt_apply_prompt_prefix {
-    [[ $PROMPT == \(wt\)* ]] || PROMPT="(wt) $PROMPT"
+    local prefix=${_WT_PROMPT_PREFIX-"(wt) "}
+    [[ -z $prefix || $PROMPT == "$prefix"* ]] || PROMPT="$prefix$PROMPT"
 }
 
 function _wt_install_prompt_prefix {
@@ -120,6 +212,13 @@ function _wt_install_prompt_prefix {
     precmd_functions+=(_wt_apply_prompt_prefix)
 }
 
+# Run the configured shell init once, after the user's .zshrc has loaded.
+function _wt_run_shell_init {
+    precmd_functions=(${precmd_functions:#_wt_run_shell_init})
+    unfunction _wt_run_shell_init
+    [[ -n $_WT_SHELL_INIT ]] && eval "$_WT_SHELL_INIT"
+}
+
 # Wrap compinit to replay after it runs.
 function compinit {
     unfunction compinit
@@ -129,6 +228,7 @@ function compinit {
 }
 
 _wt_install_prompt_prefix
+precmd_functions=(_wt_run_shell_init $precmd_functions)
 "#;
 
     let compdef_content = r#"# Pre-compinit compdef stub to prevent "command not found" errors.
@@ -139,7 +239,7 @@ compdef() {
 }
 "#;
 
-    std::fs::write(temp_dir.join(".zshenv"), zshenv_content)?;
+    std::fs::write(temp_dir.path().join(".zshenv"), zshenv_content)?;
     std::fs::write(functions_dir.join("compdef"), compdef_content)?;
     Ok(temp_dir)
 }
@@ -150,7 +250,7 @@ fn show_exit_status(wt_path: &Path) -> Result<()> {
     let output = Command::new("git")
         .args(["status", "--short"])
         .current_dir(wt_path)
-        .output()
+        .run()
         .context("Failed to get git status")?;
 
     let status = String::from_utf8_lossy(&output.stdout);
@@ -166,29 +266,113 @@ fn show_exit_status(wt_path: &Path) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use super::create_zsh_wrapper;
+    use super::{create_zsh_wrapper, TempRc, BASH_RCFILE, FISH_INIT_COMMAND};
     use std::fs;
     use std::process::Command;
 
-    fn zsh_available() -> bool {
-        Command::new("zsh")
+    fn shell_available(shell: &str) -> bool {
+        Command::new(shell)
             .arg("--version")
             .output()
             .map(|output| output.status.success())
             .unwrap_or(false)
     }
 
+    fn zsh_available() -> bool {
+        shell_available("zsh")
+    }
+
+    /// Start bash on the generated rcfile with `bashrc` as the user's
+    /// `~/.bashrc` (or none), run one prompt cycle and return `$PS1`.
+    fn bash_prompt_after_startup(bashrc: Option<&str>) -> String {
+        let home_dir = tempfile::TempDir::new().expect("create fake home");
+        if let Some(bashrc) = bashrc {
+            fs::write(home_dir.path().join(".bashrc"), bashrc).expect("write fake bashrc");
+        }
+        let rcfile = TempRc::file("wt-test-bashrc", BASH_RCFILE).expect("write rcfile");
+
+        let output = Command::new("bash")
+            .arg("--rcfile")
+            .arg(rcfile.path())
+            .args(["-i", "-c", "eval \"$PROMPT_COMMAND\"; printf '%s' \"$PS1\""])
+            .env("HOME", home_dir.path())
+            .env("_WT_PROMPT_PREFIX", "(wt: demo) ")
+            .output()
+            .expect("run bash startup");
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "bash startup failed: {}", stderr);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    #[test]
+    fn bash_rcfile_prefixes_prompt_without_user_bashrc() {
+        if !shell_available("bash") {
+            return;
+        }
+
+        assert!(bash_prompt_after_startup(None).starts_with("(wt: demo) "));
+    }
+
+    #[test]
+    fn bash_rcfile_keeps_prefix_when_prompt_command_rebuilds_ps1() {
+        if !shell_available("bash") {
+            return;
+        }
+
+        let prompt = bash_prompt_after_startup(Some(
+            "PS1='plain$ '\n_theme() { PS1='theme$ '; }\nPROMPT_COMMAND=_theme\n",
+        ));
+        assert_eq!(prompt, "(wt: demo) theme$ ");
+    }
+
+    #[test]
+    fn fish_init_command_prefixes_prompt() {
+        if !shell_available("fish") {
+            return;
+        }
+
+        let home_dir = tempfile::TempDir::new().expect("create fake home");
+        let output = Command::new("fish")
+            .arg("--init-command")
+            .arg(FISH_INIT_COMMAND)
+            .args(["-c", "fish_prompt"])
+            .env("HOME", home_dir.path())
+            .env("XDG_CONFIG_HOME", home_dir.path().join(".config"))
+            .env("_WT_PROMPT_PREFIX", "(wt: demo) ")
+            .output()
+            .expect("run fish startup");
+
+        assert!(String::from_utf8_lossy(&output.stdout).starts_with("(wt: demo) "));
+    }
+
     #[test]
     fn zsh_wrapper_sources_startup_files_from_original_dotdir() {
         let temp_dir = create_zsh_wrapper().expect("create zsh wrapper");
-        let zshenv = std::fs::read_to_string(temp_dir.join(".zshenv")).expect("read zshenv");
+        let zshenv = std::fs::read_to_string(temp_dir.path().join(".zshenv")).expect("read zshenv");
 
         assert!(zshenv.contains("export ZDOTDIR=\"$_WT_ORIG_ZDOTDIR\""));
         assert!(zshenv.contains("fpath=(\"$ZDOTDIR/functions\" $fpath)"));
-        assert!(temp_dir.join("functions").join("compdef").exists());
-        assert!(!temp_dir.join(".zshrc").exists());
+        assert!(temp_dir.path().join("functions").join("compdef").exists());
+        assert!(!temp_dir.path().join(".zshrc").exists());
+    }
 
-        let _ = std::fs::remove_dir_all(temp_dir);
+    #[test]
+    fn temp_rc_is_unique_and_removed_on_drop() {
+        let first = TempRc::file("wt-test-rc", "echo first").expect("create first rc");
+        let second = TempRc::file("wt-test-rc", "echo second").expect("create second rc");
+        assert_ne!(first.path(), second.path());
+
+        let first_path = first.path().to_path_buf();
+        assert_eq!(fs::read_to_string(&first_path).unwrap(), "echo first");
+        drop(first);
+        assert!(!first_path.exists());
+
+        let dir = TempRc::dir("wt-test-dir").expect("create temp dir");
+        let dir_path = dir.path().to_path_buf();
+        fs::write(dir_path.join(".zshenv"), "").unwrap();
+        drop(dir);
+        assert!(!dir_path.exists());
     }
 
     #[test]
@@ -214,7 +398,7 @@ PROMPT="demo ❯❯❯ "
             .arg("-ic")
             .arg("print -r -- \"${(j:,:)precmd_functions}\"; _wt_apply_prompt_prefix; print -r -- \"$PROMPT\"")
             .env("HOME", home_dir.path())
-            .env("ZDOTDIR", &wrapper_dir)
+            .env("ZDOTDIR", wrapper_dir.path())
             .env("_WT_ORIG_ZDOTDIR", home_dir.path())
             .output()
             .expect("run zsh startup");
@@ -230,7 +414,5 @@ PROMPT="demo ❯❯❯ "
         let stdout = String::from_utf8_lossy(&output.stdout);
         assert!(stdout.contains("_wt_apply_prompt_prefix"));
         assert!(stdout.contains("(wt) demo ❯❯❯ "));
-
-        let _ = fs::remove_dir_all(wrapper_dir);
     }
 }
@@ -1,13 +1,30 @@
-use anyhow::{Context, Result};
-use std::collections::HashSet;
-use std::path::Path;
+use clap::ValueEnum;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::{Duration, Instant};
 
+use crate::command::CommandExt;
 use crate::config::SessionConfig;
+use crate::error::{Result, WtError};
+
+/// How often a pane is re-captured while waiting for the agent to be ready.
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Session option recording the repo a session was created for.
+const OWNER_OPTION: &str = "@wt_repo";
+
+/// File in the worktree that agent output goes to with `log_agent_output`.
+pub const AGENT_LOG_FILE: &str = ".wt-agent.log";
 
 #[derive(Debug)]
 pub struct TmuxManager {
     session_name: String,
+    /// Program name whose presence in a window's first pane means the agent
+    /// is running. Without it, any non-shell command counts as active.
+    agent_program: Option<String>,
+    /// Repo recorded on the session when `create_session` makes it.
+    owner: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -17,12 +34,17 @@ pub struct TmuxWindow {
     pub pane_count: u32,
     pub active: bool,
     pub agent_status: AgentStatus,
+    /// Unix time of the window's last output, if tmux reported it.
+    pub last_activity: Option<u64>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum AgentStatus {
     Idle,
     Active,
+    /// The agent pane's process exited with a non-zero status. tmux only
+    /// keeps such panes around when `remain-on-exit` is on.
+    Stopped,
     Unknown,
 }
 
@@ -31,6 +53,7 @@ impl std::fmt::Display for AgentStatus {
         match self {
             AgentStatus::Idle => write!(f, "idle"),
             AgentStatus::Active => write!(f, "active"),
+            AgentStatus::Stopped => write!(f, "stopped"),
             AgentStatus::Unknown => write!(f, "?"),
         }
     }
@@ -40,14 +63,44 @@ impl TmuxManager {
     pub fn new(session_name: &str) -> Self {
         Self {
             session_name: session_name.to_string(),
+            agent_program: None,
+            owner: None,
         }
     }
 
+    /// Report a window's agent as active only while `program` runs in its
+    /// first pane; other non-shell commands then read as unknown.
+    pub fn with_agent_program(mut self, program: Option<&str>) -> Self {
+        self.agent_program = program.map(str::to_string);
+        self
+    }
+
+    /// Record `repo` as the owner of sessions this manager creates.
+    pub fn with_owner(mut self, repo: &Path) -> Self {
+        self.owner = Some(repo.to_path_buf());
+        self
+    }
+
+    /// The repo recorded on the session by `create_session`, or `None`
+    /// for sessions created without an owner.
+    pub fn session_owner(&self) -> Result<Option<PathBuf>> {
+        let owner = run_tmux(&[
+            "show-options",
+            "-t",
+            &self.session_name,
+            "-v",
+            "-q",
+            OWNER_OPTION,
+        ])?;
+        let owner = owner.trim();
+        Ok((!owner.is_empty()).then(|| PathBuf::from(owner)))
+    }
+
     /// Check if tmux is available on the system.
     pub fn is_available() -> bool {
         Command::new("tmux")
             .arg("-V")
-            .output()
+            .run()
             .map(|output| output.status.success())
             .unwrap_or(false)
     }
@@ -57,7 +110,7 @@ impl TmuxManager {
         if let Ok(tmux_var) = std::env::var("TMUX") {
             if let Ok(output) = Command::new("tmux")
                 .args(["display-message", "-p", "#{session_name}"])
-                .output()
+                .run()
             {
                 if output.status.success() {
                     let current_session = String::from_utf8_lossy(&output.stdout);
@@ -76,12 +129,17 @@ impl TmuxManager {
         std::env::var("TMUX").is_ok()
     }
 
+    /// Name of the tmux session this process is running in.
+    pub fn current_session() -> Result<String> {
+        run_tmux(&["display-message", "-p", "#{session_name}"]).map(|name| name.trim().to_string())
+    }
+
     /// Check if the session already exists.
     pub fn session_exists(&self) -> Result<bool> {
         let output = Command::new("tmux")
             .args(["has-session", "-t", &self.session_name])
-            .output()
-            .context("Failed to check tmux session")?;
+            .run()
+            .map_err(|e| WtError::io("Failed to check tmux session", e))?;
 
         Ok(output.status.success())
     }
@@ -96,8 +154,8 @@ impl TmuxManager {
                 "-p",
                 "#{session_attached}",
             ])
-            .output()
-            .context("Failed to query session attachment")?;
+            .run()
+            .map_err(|e| WtError::io("Failed to query session attachment", e))?;
 
         if !output.status.success() {
             return Ok(false);
@@ -112,27 +170,25 @@ impl TmuxManager {
 
     /// Create a new session with an initial window.
     pub fn create_session(&self, window_name: &str, cwd: &Path) -> Result<()> {
-        let output = Command::new("tmux")
-            .args([
-                "new-session",
-                "-d",
-                "-s",
+        run_tmux(&[
+            "new-session",
+            "-d",
+            "-s",
+            &self.session_name,
+            "-n",
+            window_name,
+            "-c",
+            &cwd.to_string_lossy(),
+        ])?;
+        if let Some(owner) = &self.owner {
+            run_tmux(&[
+                "set-option",
+                "-t",
                 &self.session_name,
-                "-n",
-                window_name,
-                "-c",
-                &cwd.to_string_lossy(),
-            ])
-            .output()
-            .context("Failed to create tmux session")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to create session: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
+                OWNER_OPTION,
+                &owner.to_string_lossy(),
+            ])?;
         }
-
         Ok(())
     }
 
@@ -140,11 +196,11 @@ impl TmuxManager {
     pub fn attach(&self) -> Result<()> {
         let status = Command::new("tmux")
             .args(["attach-session", "-t", &self.session_name])
-            .status()
-            .context("Failed to attach to tmux session")?;
+            .run_status()
+            .map_err(|e| WtError::io("Failed to attach to tmux session", e))?;
 
         if !status.success() {
-            anyhow::bail!("Failed to attach to session");
+            return Err(WtError::Attach(self.session_name.clone()));
         }
 
         Ok(())
@@ -155,11 +211,11 @@ impl TmuxManager {
         if Self::is_inside_tmux() {
             let status = Command::new("tmux")
                 .args(["switch-client", "-t", &self.session_name])
-                .status()
-                .context("Failed to switch tmux client")?;
+                .run_status()
+                .map_err(|e| WtError::io("Failed to switch tmux client", e))?;
 
             if !status.success() {
-                anyhow::bail!("Failed to switch client to session '{}'", self.session_name);
+                return Err(WtError::Attach(self.session_name.clone()));
             }
 
             Ok(())
@@ -170,18 +226,7 @@ impl TmuxManager {
 
     /// Kill the whole session.
     pub fn kill_session(&self) -> Result<()> {
-        let output = Command::new("tmux")
-            .args(["kill-session", "-t", &self.session_name])
-            .output()
-            .context("Failed to kill tmux session")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to kill session: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
-
+        run_tmux(&["kill-session", "-t", &self.session_name])?;
         Ok(())
     }
 
@@ -189,8 +234,8 @@ impl TmuxManager {
     pub fn live_session_names() -> Result<HashSet<String>> {
         let output = Command::new("tmux")
             .args(["list-sessions", "-F", "#{session_name}"])
-            .output()
-            .context("Failed to list tmux sessions")?;
+            .run()
+            .map_err(|e| WtError::io("Failed to list tmux sessions", e))?;
 
         if !output.status.success() {
             return Ok(HashSet::new());
@@ -205,35 +250,26 @@ impl TmuxManager {
     /// Create a new window in the session.
     pub fn create_window(&self, name: &str, cwd: &Path) -> Result<u32> {
         let target = self.next_window_target();
-        let output = Command::new("tmux")
-            .args([
-                "new-window",
-                "-t",
-                &target,
-                "-n",
-                name,
-                "-c",
-                &cwd.to_string_lossy(),
-                "-P",
-                "-F",
-                "#{window_index}",
-            ])
-            .output()
-            .context("Failed to create tmux window")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to create window: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
-
-        let index_str = String::from_utf8_lossy(&output.stdout);
-        let index: u32 = index_str
+        let output = run_tmux(&[
+            "new-window",
+            "-t",
+            &target,
+            "-n",
+            name,
+            "-c",
+            &cwd.to_string_lossy(),
+            "-P",
+            "-F",
+            "#{window_index}",
+        ])?;
+
+        output
             .trim()
             .parse()
-            .context("Failed to parse window index")?;
-        Ok(index)
+            .map_err(|_| WtError::UnexpectedOutput {
+                command: "tmux new-window".to_string(),
+                output,
+            })
     }
 
     /// Target the next unused window index in this session.
@@ -244,36 +280,21 @@ impl TmuxManager {
     /// Kill a window by name.
     pub fn kill_window(&self, name: &str) -> Result<()> {
         let target = format!("{}:{}", self.session_name, name);
-        let output = Command::new("tmux")
-            .args(["kill-window", "-t", &target])
-            .output()
-            .context("Failed to kill tmux window")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to kill window: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
+        run_tmux(&["kill-window", "-t", &target])?;
+        Ok(())
+    }
 
+    /// Rename a window.
+    pub fn rename_window(&self, old: &str, new: &str) -> Result<()> {
+        let target = format!("{}:{}", self.session_name, old);
+        run_tmux(&["rename-window", "-t", &target, new])?;
         Ok(())
     }
 
     /// Switch to a window by name.
     pub fn select_window(&self, name: &str) -> Result<()> {
         let target = format!("{}:{}", self.session_name, name);
-        let output = Command::new("tmux")
-            .args(["select-window", "-t", &target])
-            .output()
-            .context("Failed to select window")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to select window: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
-
+        run_tmux(&["select-window", "-t", &target])?;
         Ok(())
     }
 
@@ -285,33 +306,38 @@ impl TmuxManager {
                 "-t",
                 &self.session_name,
                 "-F",
-                "#{window_index}|#{window_name}|#{window_panes}|#{window_active}",
+                "#{window_index}|#{window_name}|#{window_panes}|#{window_active}|#{window_activity}",
             ])
-            .output()
-            .context("Failed to list tmux windows")?;
+            .run()
+            .map_err(|e| WtError::io("Failed to list tmux windows", e))?;
 
         if !output.status.success() {
             return Ok(vec![]);
         }
 
+        let pane_statuses = self.first_pane_statuses();
         let stdout = String::from_utf8_lossy(&output.stdout);
         let windows = stdout
             .lines()
             .filter_map(|line| {
                 let parts: Vec<&str> = line.split('|').collect();
-                if parts.len() != 4 {
+                if parts.len() != 5 {
                     return None;
                 }
 
-                let name = parts[1].to_string();
-                let agent_status = self.get_agent_status(&name).unwrap_or(AgentStatus::Unknown);
+                let index = parts[0].parse().ok()?;
+                let agent_status = pane_statuses
+                    .get(&index)
+                    .copied()
+                    .unwrap_or(AgentStatus::Unknown);
 
                 Some(TmuxWindow {
-                    index: parts[0].parse().ok()?,
-                    name,
+                    index,
+                    name: parts[1].to_string(),
                     pane_count: parts[2].parse().ok()?,
                     active: parts[3] == "1",
                     agent_status,
+                    last_activity: parts[4].parse().ok(),
                 })
             })
             .collect();
@@ -319,140 +345,194 @@ impl TmuxManager {
         Ok(windows)
     }
 
-    /// Get the agent status for a window (checks pane 0).
-    fn get_agent_status(&self, window: &str) -> Result<AgentStatus> {
-        let target = format!("{}:{}.0", self.session_name, window);
+    /// The agent status of each window's first pane, keyed by window
+    /// index, from a single `list-panes` call for the whole session.
+    fn first_pane_statuses(&self) -> HashMap<u32, AgentStatus> {
         let output = Command::new("tmux")
             .args([
-                "display-message",
+                "list-panes",
+                "-s",
                 "-t",
-                &target,
-                "-p",
-                "#{pane_current_command}",
+                &self.session_name,
+                "-F",
+                "#{window_index}|#{pane_index}|#{pane_dead}|#{pane_dead_status}|#{pane_current_command}",
             ])
-            .output()
-            .context("Failed to get pane command")?;
-
-        if !output.status.success() {
-            return Ok(AgentStatus::Unknown);
+            .run();
+
+        match output {
+            Ok(output) if output.status.success() => parse_first_pane_statuses(
+                &String::from_utf8_lossy(&output.stdout),
+                self.agent_program.as_deref(),
+            ),
+            _ => HashMap::new(),
         }
+    }
 
-        let cmd = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        let shells = ["bash", "zsh", "sh", "fish", "ksh", "tcsh", "dash"];
-        if shells.iter().any(|shell| cmd == *shell) {
-            Ok(AgentStatus::Idle)
-        } else if cmd.is_empty() {
-            Ok(AgentStatus::Unknown)
-        } else {
-            Ok(AgentStatus::Active)
-        }
+    /// Split the current pane horizontally (left/right), returning the
+    /// new pane's index.
+    pub fn split_window_horizontal(&self, window: &str, cwd: &Path) -> Result<u32> {
+        self.split_window(window, "-h", cwd)
+    }
+
+    /// Split the current pane vertically (top/bottom), returning the new
+    /// pane's index.
+    pub fn split_window_vertical(&self, window: &str, cwd: &Path) -> Result<u32> {
+        self.split_window(window, "-v", cwd)
     }
 
-    /// Split the current pane horizontally (left/right).
-    pub fn split_window_horizontal(&self, window: &str, cwd: &Path) -> Result<()> {
+    fn split_window(&self, window: &str, direction: &str, cwd: &Path) -> Result<u32> {
         let target = format!("{}:{}", self.session_name, window);
-        let output = Command::new("tmux")
-            .args([
-                "split-window",
-                "-h",
-                "-t",
-                &target,
-                "-c",
-                &cwd.to_string_lossy(),
-            ])
-            .output()
-            .context("Failed to split window horizontally")?;
+        let output = run_tmux(&[
+            "split-window",
+            direction,
+            "-t",
+            &target,
+            "-c",
+            &cwd.to_string_lossy(),
+            "-P",
+            "-F",
+            "#{pane_index}",
+        ])?;
+
+        output
+            .trim()
+            .parse()
+            .map_err(|_| WtError::UnexpectedOutput {
+                command: "tmux split-window".to_string(),
+                output,
+            })
+    }
 
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to split window: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
+    /// Select a specific pane in a window.
+    pub fn select_pane(&self, window: &str, pane: u32) -> Result<()> {
+        let target = format!("{}:{}.{}", self.session_name, window, pane);
+        run_tmux(&["select-pane", "-t", &target])?;
+        Ok(())
+    }
 
+    /// Send keys to a specific pane.
+    pub fn send_keys(&self, window: &str, pane: u32, keys: &str) -> Result<()> {
+        let target = format!("{}:{}.{}", self.session_name, window, pane);
+        run_tmux(&["send-keys", "-t", &target, keys, "Enter"])?;
         Ok(())
     }
 
-    /// Split the current pane vertically (top/bottom).
-    pub fn split_window_vertical(&self, window: &str, cwd: &Path) -> Result<()> {
+    /// Arrange a window's panes with one of tmux's named layouts
+    /// (`even-horizontal`, `main-vertical`, `tiled`, ...).
+    pub fn select_layout(&self, window: &str, layout: &str) -> Result<()> {
         let target = format!("{}:{}", self.session_name, window);
-        let output = Command::new("tmux")
-            .args([
-                "split-window",
-                "-v",
-                "-t",
-                &target,
-                "-c",
-                &cwd.to_string_lossy(),
-            ])
-            .output()
-            .context("Failed to split window vertically")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to split window: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
-
+        run_tmux(&["select-layout", "-t", &target, layout])?;
         Ok(())
     }
 
-    /// Select a specific pane in a window.
-    pub fn select_pane(&self, window: &str, pane: u32) -> Result<()> {
+    /// Set a pane's width to `percent` of its window.
+    pub fn resize_pane_width(&self, window: &str, pane: u32, percent: u8) -> Result<()> {
         let target = format!("{}:{}.{}", self.session_name, window, pane);
-        let output = Command::new("tmux")
-            .args(["select-pane", "-t", &target])
-            .output()
-            .context("Failed to select pane")?;
+        run_tmux(&["resize-pane", "-t", &target, "-x", &format!("{}%", percent)])?;
+        Ok(())
+    }
 
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to select pane: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
+    /// Apply a `layout` setting: `N%` sizes the agent pane, anything else
+    /// is passed to tmux as a layout name.
+    pub fn apply_layout(&self, window: &str, layout: &str) -> Result<()> {
+        match parse_layout(layout)? {
+            Some(percent) => self.resize_pane_width(window, 0, percent),
+            None => self.select_layout(window, layout),
         }
+    }
 
+    /// Send Ctrl-C to a specific pane.
+    pub fn send_interrupt(&self, window: &str, pane: u32) -> Result<()> {
+        let target = format!("{}:{}.{}", self.session_name, window, pane);
+        run_tmux(&["send-keys", "-t", &target, "C-c"])?;
         Ok(())
     }
 
-    /// Send keys to a specific pane.
-    pub fn send_keys(&self, window: &str, pane: u32, keys: &str) -> Result<()> {
+    /// Append everything a pane prints from now on to `path`. A pane that
+    /// is already being piped is left alone.
+    pub fn pipe_pane_to_file(&self, window: &str, pane: u32, path: &Path) -> Result<()> {
         let target = format!("{}:{}.{}", self.session_name, window, pane);
-        let output = Command::new("tmux")
-            .args(["send-keys", "-t", &target, keys, "Enter"])
-            .output()
-            .context("Failed to send keys")?;
+        let command = format!("cat >> {}", shell_quote(&path.to_string_lossy()));
+        run_tmux(&["pipe-pane", "-o", "-t", &target, &command])?;
+        Ok(())
+    }
 
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to send keys: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
+    /// Current working directory of the shell in a pane.
+    pub fn pane_current_path(&self, window: &str, pane: u32) -> Result<PathBuf> {
+        let target = format!("{}:{}.{}", self.session_name, window, pane);
+        run_tmux(&[
+            "display-message",
+            "-t",
+            &target,
+            "-p",
+            "#{pane_current_path}",
+        ])
+        .map(|path| PathBuf::from(path.trim()))
+    }
 
-        Ok(())
+    /// Capture the visible contents of a pane.
+    pub fn capture_pane(&self, window: &str, pane: u32) -> Result<String> {
+        let target = format!("{}:{}.{}", self.session_name, window, pane);
+        run_tmux(&["capture-pane", "-p", "-t", &target])
+    }
+
+    /// Poll a pane until its contents contain `pattern`. Returns `false` if
+    /// the timeout elapses first.
+    pub fn wait_for_pane_ready(
+        &self,
+        window: &str,
+        pane: u32,
+        pattern: &str,
+        timeout: Duration,
+    ) -> Result<bool> {
+        wait_until_ready(
+            || self.capture_pane(window, pane),
+            pattern,
+            timeout,
+            READY_POLL_INTERVAL,
+        )
     }
 
-    /// Setup the worktree layout based on pane count.
+    /// Setup the worktree layout based on pane count, then apply `layout`
+    /// (see `apply_layout`), or else `main_pane_percent`, when there is
+    /// more than one pane.
     pub fn setup_worktree_layout(
         &self,
         window: &str,
         cwd: &Path,
         panes: u8,
+        layout: Option<&str>,
         config: &SessionConfig,
     ) -> Result<()> {
-        self.split_window_horizontal(window, cwd)?;
+        match panes {
+            1 => {
+                self.start_agent(window, cwd, config)?;
+            }
+            3 => {
+                self.split_window_horizontal(window, cwd)?;
+                self.select_pane(window, 0)?;
+                self.split_window_vertical(window, cwd)?;
+                self.start_agent(window, cwd, config)?;
+                self.send_keys(window, 1, &config.editor_cmd)?;
+                self.select_pane(window, 2)?;
+            }
+            _ => {
+                self.split_window_horizontal(window, cwd)?;
+                self.start_agent(window, cwd, config)?;
+                self.select_pane(window, 1)?;
+            }
+        }
 
-        if panes == 3 {
-            self.select_pane(window, 0)?;
-            self.split_window_vertical(window, cwd)?;
-            self.send_keys(window, 0, &config.agent_cmd)?;
-            self.send_keys(window, 1, &config.editor_cmd)?;
-            self.select_pane(window, 2)?;
-        } else {
-            self.send_keys(window, 0, &config.agent_cmd)?;
-            self.select_pane(window, 1)?;
+        if panes > 1 {
+            match layout {
+                Some(layout) => self.apply_layout(window, layout)?,
+                None => {
+                    let percent = config.effective_main_pane_percent();
+                    if percent != 50 {
+                        self.resize_pane_width(window, 0, percent)?;
+                    }
+                }
+            }
         }
 
         Ok(())
@@ -465,7 +545,11 @@ impl TmuxManager {
         panes: u8,
         config: &SessionConfig,
     ) -> Result<()> {
-        self.send_keys("agent", 0, &config.agent_cmd)?;
+        self.start_agent("agent", cwd, config)?;
+        if panes == 1 {
+            return Ok(());
+        }
+
         self.create_window("shell", cwd)?;
 
         if panes == 3 {
@@ -477,20 +561,270 @@ impl TmuxManager {
         Ok(())
     }
 
+    /// Run `agent_cmd` in pane 0 of `window`, logging its output to
+    /// `AGENT_LOG_FILE` in `cwd` when `log_agent_output` is on.
+    fn start_agent(&self, window: &str, cwd: &Path, config: &SessionConfig) -> Result<()> {
+        if config.log_agent_output {
+            self.pipe_pane_to_file(window, 0, &cwd.join(AGENT_LOG_FILE))?;
+        }
+        self.send_keys(window, 0, &config.agent_cmd)
+    }
+
     /// Get session name.
     pub fn session_name(&self) -> &str {
         &self.session_name
     }
 }
 
+/// Run `tmux <args>` and return its stdout. A non-zero exit becomes
+/// `WtError::Tmux` carrying tmux's stderr.
+fn run_tmux(args: &[&str]) -> Result<String> {
+    let output = Command::new("tmux")
+        .args(args)
+        .run()
+        .map_err(|e| WtError::io("Failed to execute tmux", e))?;
+
+    if !output.status.success() {
+        return Err(WtError::Tmux {
+            args: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Repeatedly call `capture` until the returned buffer contains `pattern`
+/// or `timeout` elapses. The buffer is always checked at least once.
+pub fn wait_until_ready(
+    mut capture: impl FnMut() -> Result<String>,
+    pattern: &str,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<bool> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if capture()?.contains(pattern) {
+            return Ok(true);
+        }
+        if Instant::now() >= deadline {
+            return Ok(false);
+        }
+        std::thread::sleep(poll_interval);
+    }
+}
+
+/// Parse `window_index|pane_index|pane_dead|pane_dead_status|command`
+/// lines, keeping the status of the lowest-numbered pane in each window
+/// (the agent pane). A dead pane's status comes from its exit code.
+fn parse_first_pane_statuses(
+    output: &str,
+    agent_program: Option<&str>,
+) -> HashMap<u32, AgentStatus> {
+    let mut first: HashMap<u32, (u32, AgentStatus)> = HashMap::new();
+    for line in output.lines() {
+        let parts: Vec<&str> = line.splitn(5, '|').collect();
+        let [window, pane, dead, dead_status, cmd] = parts[..] else {
+            continue;
+        };
+        let (Ok(window), Ok(pane)) = (window.parse::<u32>(), pane.parse::<u32>()) else {
+            continue;
+        };
+
+        let status = if dead == "1" {
+            agent_status_for_exit(dead_status.parse().ok())
+        } else {
+            agent_status_for_command(cmd.trim(), agent_program)
+        };
+        let entry = first.entry(window).or_insert((pane, status));
+        if pane < entry.0 {
+            *entry = (pane, status);
+        }
+    }
+
+    first
+        .into_iter()
+        .map(|(window, (_, status))| (window, status))
+        .collect()
+}
+
+/// A dead pane whose process exited cleanly is finished; any other exit
+/// means the agent stopped unexpectedly.
+fn agent_status_for_exit(exit_status: Option<i32>) -> AgentStatus {
+    match exit_status {
+        Some(0) => AgentStatus::Idle,
+        _ => AgentStatus::Stopped,
+    }
+}
+
+/// `Some(percent)` for an `N%` layout, `None` for a tmux layout name.
+fn parse_layout(layout: &str) -> Result<Option<u8>> {
+    let Some(percent) = layout.strip_suffix('%') else {
+        return Ok(None);
+    };
+    match percent.parse::<u8>() {
+        Ok(percent) if (1..100).contains(&percent) => Ok(Some(percent)),
+        _ => Err(WtError::InvalidLayout(layout.to_string())),
+    }
+}
+
+/// Quote `s` as a single POSIX shell word.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// A pane running a bare shell is idle. With a known agent program, only
+/// that program is an active agent and anything else (an editor, a pager)
+/// is unknown; without one, any other command counts as active.
+fn agent_status_for_command(cmd: &str, agent_program: Option<&str>) -> AgentStatus {
+    let shells = ["bash", "zsh", "sh", "fish", "ksh", "tcsh", "dash"];
+    if shells.contains(&cmd) {
+        AgentStatus::Idle
+    } else if cmd.is_empty() {
+        AgentStatus::Unknown
+    } else {
+        match agent_program {
+            Some(program) if program != cmd => AgentStatus::Unknown,
+            _ => AgentStatus::Active,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_first_pane_statuses_keeps_lowest_pane() {
+        let output = "0|0|0||zsh\n1|1|0||nvim\n1|0|0||claude\n2|1|0||bash\nbogus\n";
+        let statuses = parse_first_pane_statuses(output, Some("claude"));
+
+        assert_eq!(statuses.len(), 3);
+        assert_eq!(statuses[&0], AgentStatus::Idle);
+        assert_eq!(statuses[&1], AgentStatus::Active);
+        // pane-base-index 1: the first pane is pane 1
+        assert_eq!(statuses[&2], AgentStatus::Idle);
+    }
+
+    #[test]
+    fn test_parse_first_pane_statuses_flags_dead_panes() {
+        let output = "0|0|1|1|claude\n1|0|1|0|claude\n2|0|0||claude\n";
+        let statuses = parse_first_pane_statuses(output, None);
+
+        assert_eq!(statuses[&0], AgentStatus::Stopped);
+        assert_eq!(statuses[&1], AgentStatus::Idle);
+        assert_eq!(statuses[&2], AgentStatus::Active);
+    }
+
+    #[test]
+    fn test_agent_status_for_command() {
+        assert_eq!(agent_status_for_command("zsh", None), AgentStatus::Idle);
+        assert_eq!(
+            agent_status_for_command("claude", None),
+            AgentStatus::Active
+        );
+        assert_eq!(agent_status_for_command("", None), AgentStatus::Unknown);
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        assert_eq!(parse_layout("main-vertical").unwrap(), None);
+        assert_eq!(parse_layout("70%").unwrap(), Some(70));
+        assert!(matches!(
+            parse_layout("100%"),
+            Err(WtError::InvalidLayout(_))
+        ));
+        assert!(matches!(
+            parse_layout("wide%"),
+            Err(WtError::InvalidLayout(_))
+        ));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(
+            shell_quote("/tmp/wt/.wt-agent.log"),
+            "'/tmp/wt/.wt-agent.log'"
+        );
+        assert_eq!(shell_quote("it's here"), "'it'\\''s here'");
+    }
+
+    #[test]
+    fn test_agent_status_for_command_matches_agent_program() {
+        let agent = Some("claude");
+        assert_eq!(
+            agent_status_for_command("claude", agent),
+            AgentStatus::Active
+        );
+        assert_eq!(agent_status_for_command("vim", agent), AgentStatus::Unknown);
+        assert_eq!(
+            agent_status_for_command("less", agent),
+            AgentStatus::Unknown
+        );
+        assert_eq!(agent_status_for_command("fish", agent), AgentStatus::Idle);
+    }
+
     #[test]
     fn test_is_available() {
-        let available = TmuxManager::is_available();
-        assert!(available || !available);
+        // Only checks that probing for tmux does not panic.
+        let _ = TmuxManager::is_available();
+    }
+
+    #[test]
+    fn test_wait_until_ready_stops_at_first_matching_buffer() {
+        let buffers = ["", "Loading...", "Welcome\n> ", "never read"];
+        let mut calls = 0;
+
+        let ready = wait_until_ready(
+            || {
+                let buffer = buffers[calls].to_string();
+                calls += 1;
+                Ok(buffer)
+            },
+            "> ",
+            Duration::from_secs(5),
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert!(ready);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_wait_until_ready_times_out() {
+        let mut calls = 0;
+
+        let ready = wait_until_ready(
+            || {
+                calls += 1;
+                Ok("Loading...".to_string())
+            },
+            "> ",
+            Duration::ZERO,
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert!(!ready);
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_wait_until_ready_propagates_capture_errors() {
+        let result = wait_until_ready(
+            || {
+                Err(WtError::Tmux {
+                    args: "capture-pane".to_string(),
+                    stderr: "can't find pane".to_string(),
+                })
+            },
+            "> ",
+            Duration::from_secs(5),
+            Duration::ZERO,
+        );
+
+        assert!(result.is_err());
     }
 
     #[test]
@@ -1,15 +1,141 @@
-use anyhow::{Context, Result};
+use std::ffi::OsStr;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
 
+use crate::command::CommandExt;
+use crate::error::{Result, WtError};
+use crate::git::Git;
+
+/// Upper bound on concurrent `git status` processes when checking
+/// worktrees for uncommitted changes.
+const STATUS_WORKERS: usize = 4;
+
+/// Encode a branch name as a single directory name. `/` becomes `--` (so
+/// `feat/x` is still `feat--x`), and the escapes that make this reversible
+/// are `%` -> `%25` plus `-` -> `%2D` wherever a literal dash touches
+/// another dash or a slash. Any run of dashes in the output therefore comes
+/// from slashes only.
 fn sanitize_for_path(name: &str) -> String {
-    name.replace('/', "--")
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len());
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            '/' => out.push_str("--"),
+            '%' => out.push_str("%25"),
+            '-' => {
+                let touches = |j: Option<usize>| {
+                    j.and_then(|j| chars.get(j))
+                        .is_some_and(|&n| n == '-' || n == '/')
+                };
+                if touches(i.checked_sub(1)) || touches(Some(i + 1)) {
+                    out.push_str("%2D");
+                } else {
+                    out.push('-');
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
 }
 
+/// Inverse of `sanitize_for_path`. Malformed `%` escapes are kept as-is.
 fn unsanitize_from_path(name: &str) -> String {
-    name.replace("--", "/")
+    let bytes = name.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"--") {
+            out.push(b'/');
+            i += 2;
+            continue;
+        }
+        if bytes[i] == b'%' {
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            if let Some(byte) = hex {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The directory `create_worktree` uses for `name` under `worktree_dir`.
+pub fn worktree_path_for(worktree_dir: &Path, name: &str) -> PathBuf {
+    worktree_dir.join(sanitize_for_path(name))
+}
+
+/// Longest slug `issue_branch_name` keeps from an issue title.
+const MAX_SLUG_LEN: usize = 50;
+
+/// Lowercase `title` and turn every run of non-alphanumeric characters
+/// into a single `-`, e.g. `Fix: auth bug!` -> `fix-auth-bug`.
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    if slug.len() > MAX_SLUG_LEN {
+        slug.truncate(MAX_SLUG_LEN);
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Branch name for a GitHub issue: `123-fix-auth-bug`, or `issue-123` when
+/// the title is unknown or has nothing to slugify.
+pub fn issue_branch_name(number: u64, title: Option<&str>) -> String {
+    match title.map(slugify).filter(|slug| !slug.is_empty()) {
+        Some(slug) => format!("{}-{}", number, slug),
+        None => format!("issue-{}", number),
+    }
+}
+
+/// Match `name` against a shell-style glob. `*` matches any run of
+/// characters (including `/`, since worktree names are not paths) and `?`
+/// matches exactly one character.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 fn parse_wt_copy_paths(repo_path: &Path) -> Vec<PathBuf> {
@@ -38,7 +164,45 @@ fn parse_wt_copy_paths(repo_path: &Path) -> Vec<PathBuf> {
     paths
 }
 
-fn symlink_wt_copy_files(repo_path: &Path, worktree_path: &Path) {
+/// Outcome of linking the `# wt copy` entries into a new worktree, as
+/// paths relative to the repo root.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CopySummary {
+    /// Newly created (or re-pointed stale) symlinks.
+    pub linked: Vec<PathBuf>,
+    /// Already symlinked to the right source.
+    pub skipped: Vec<PathBuf>,
+    /// Left alone because something else is in the way, or linking failed.
+    pub conflicts: Vec<PathBuf>,
+}
+
+impl CopySummary {
+    /// One line for `wt new`, e.g. `linked 2 '# wt copy' path(s): .env,
+    /// .secrets (1 already linked)`. Conflicts get their own warnings, so
+    /// `None` when nothing was linked or already in place.
+    pub fn summary_line(&self) -> Option<String> {
+        if self.linked.is_empty() && self.skipped.is_empty() {
+            return None;
+        }
+        let names: Vec<_> = self
+            .linked
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        let mut line = format!("linked {} '# wt copy' path(s)", self.linked.len());
+        if !names.is_empty() {
+            line.push_str(&format!(": {}", names.join(", ")));
+        }
+        if !self.skipped.is_empty() {
+            line.push_str(&format!(" ({} already linked)", self.skipped.len()));
+        }
+        Some(line)
+    }
+}
+
+fn symlink_wt_copy_files(repo_path: &Path, worktree_path: &Path) -> CopySummary {
+    let mut summary = CopySummary::default();
+
     for rel_path in parse_wt_copy_paths(repo_path) {
         let src = repo_path.join(&rel_path);
         let dst = worktree_path.join(&rel_path);
@@ -47,6 +211,26 @@ fn symlink_wt_copy_files(repo_path: &Path, worktree_path: &Path) {
             continue;
         }
 
+        match fs::symlink_metadata(&dst) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                if fs::read_link(&dst).is_ok_and(|target| target == src) {
+                    summary.skipped.push(rel_path);
+                    continue;
+                }
+                // Stale link pointing elsewhere: safe to replace
+                if fs::remove_file(&dst).is_err() {
+                    summary.conflicts.push(rel_path);
+                    continue;
+                }
+            }
+            Ok(_) => {
+                // A real file or directory: never clobber it
+                summary.conflicts.push(rel_path);
+                continue;
+            }
+            Err(_) => {}
+        }
+
         // Create parent directories if needed
         if let Some(parent) = dst.parent() {
             let _ = fs::create_dir_all(parent);
@@ -55,23 +239,64 @@ fn symlink_wt_copy_files(repo_path: &Path, worktree_path: &Path) {
         // Create symlink (Unix)
         #[cfg(unix)]
         {
-            let _ = std::os::unix::fs::symlink(&src, &dst);
+            match std::os::unix::fs::symlink(&src, &dst) {
+                Ok(()) => summary.linked.push(rel_path),
+                Err(_) => summary.conflicts.push(rel_path),
+            }
         }
     }
+
+    summary
+}
+
+/// Resolve `.` and `..` components without touching the filesystem, so a
+/// not-yet-created dir like `<repo>/../trees` compares correctly.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// `path` with symlinks resolved when it exists (git may report
+/// `/private/tmp/...` for `/tmp/...`), lexically normalized otherwise.
+fn canonical_or_lexical(path: &Path) -> PathBuf {
+    path.canonicalize()
+        .unwrap_or_else(|_| normalize_lexically(path))
+}
+
+/// The worktree dir relative to the repo root, or `None` when it lives
+/// outside the repo (or is the root itself) and so needs no ignore entry.
+fn worktree_dir_pattern(repo_path: &Path, worktree_dir: &Path) -> Option<String> {
+    let relative = normalize_lexically(worktree_dir)
+        .strip_prefix(normalize_lexically(repo_path))
+        .ok()?
+        .to_str()?
+        .to_string();
+    (!relative.is_empty()).then_some(relative)
 }
 
 pub fn ensure_worktrees_in_gitignore(repo_path: &Path, worktree_dir: &Path) -> Result<()> {
     let gitignore_path = repo_path.join(".gitignore");
 
-    // Get the directory name relative to repo root for gitignore
-    let pattern = worktree_dir
-        .strip_prefix(repo_path)
-        .ok()
-        .and_then(|p| p.to_str())
-        .unwrap_or(".worktrees");
+    // Worktrees outside the repo can't show up in `git status`
+    let Some(pattern) = worktree_dir_pattern(repo_path, worktree_dir) else {
+        return Ok(());
+    };
+    let pattern = pattern.as_str();
 
     if gitignore_path.exists() {
-        let content = fs::read_to_string(&gitignore_path).context("Failed to read .gitignore")?;
+        let content = fs::read_to_string(&gitignore_path)
+            .map_err(|e| WtError::io("Failed to read .gitignore", e))?;
 
         if content.lines().any(|line| line.trim() == pattern) {
             return Ok(());
@@ -80,14 +305,15 @@ pub fn ensure_worktrees_in_gitignore(repo_path: &Path, worktree_dir: &Path) -> R
         let mut file = OpenOptions::new()
             .append(true)
             .open(&gitignore_path)
-            .context("Failed to open .gitignore")?;
+            .map_err(|e| WtError::io("Failed to open .gitignore", e))?;
 
         if !content.is_empty() && !content.ends_with('\n') {
             file.write_all(b"\n")
-                .context("Failed to write newline to .gitignore")?;
+                .map_err(|e| WtError::io("Failed to write newline to .gitignore", e))?;
         }
 
-        writeln!(file, "{}", pattern).context("Failed to write to .gitignore")?;
+        writeln!(file, "{}", pattern)
+            .map_err(|e| WtError::io("Failed to write to .gitignore", e))?;
         return Ok(());
     }
 
@@ -95,47 +321,87 @@ pub fn ensure_worktrees_in_gitignore(repo_path: &Path, worktree_dir: &Path) -> R
         .create(true)
         .append(true)
         .open(&gitignore_path)
-        .context("Failed to open .gitignore")?;
+        .map_err(|e| WtError::io("Failed to open .gitignore", e))?;
 
-    writeln!(file, "{}", pattern).context("Failed to write to .gitignore")?;
+    writeln!(file, "{}", pattern).map_err(|e| WtError::io("Failed to write to .gitignore", e))?;
 
     Ok(())
 }
 
-pub fn check_not_in_worktree(path: &Path) -> Result<()> {
-    let mut current = path;
-    while let Some(parent) = current.parent() {
-        if current
-            .file_name()
-            .map(|n| n == ".worktrees")
-            .unwrap_or(false)
-        {
-            anyhow::bail!("Cannot create nested worktrees: already inside a .worktrees directory");
-        }
-        current = parent;
+/// Add `pattern` to the repo's `info/exclude`, which all its worktrees
+/// share, unless it is already listed. Unlike `.gitignore` this touches no
+/// tracked file.
+pub fn ensure_git_excluded(repo_path: &Path, pattern: &str) -> Result<()> {
+    let exclude_path =
+        repo_path.join(Git::new(repo_path).rev_parse(&["--git-path", "info/exclude"])?);
+
+    let content = match fs::read_to_string(&exclude_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(WtError::io("Failed to read info/exclude", e)),
+    };
+    if content.lines().any(|line| line.trim() == pattern) {
+        return Ok(());
+    }
+
+    if let Some(parent) = exclude_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| WtError::io("Failed to create info directory", e))?;
     }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&exclude_path)
+        .map_err(|e| WtError::io("Failed to open info/exclude", e))?;
+    let separator = if content.is_empty() || content.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    writeln!(file, "{}{}", separator, pattern)
+        .map_err(|e| WtError::io("Failed to write to info/exclude", e))?;
+
     Ok(())
 }
 
-pub fn get_current_worktree_name(path: &Path) -> Result<String> {
-    let output = Command::new("git")
-        .args(["rev-parse", "--git-dir"])
-        .current_dir(path)
-        .output()
-        .context("Failed to execute git rev-parse")?;
-
-    if !output.status.success() {
-        anyhow::bail!("Not a git repository");
+/// Refuse to create worktrees from inside one. `worktree_dir` is the
+/// configured dir resolved against `path`: a dir inside the checkout (e.g.
+/// `.worktrees` or `my-trees`) trips on any ancestor ending in it, since from
+/// within a worktree it resolves relative to that worktree; a dir outside
+/// the checkout trips when `path` is under it.
+pub fn check_not_in_worktree(path: &Path, worktree_dir: &Path) -> Result<()> {
+    let path = canonical_or_lexical(path);
+    let worktree_dir = canonical_or_lexical(worktree_dir);
+    let nested = match worktree_dir.strip_prefix(&path) {
+        Ok(relative) if relative.as_os_str().is_empty() => false,
+        Ok(relative) => path.ancestors().any(|dir| dir.ends_with(relative)),
+        Err(_) => path.starts_with(&worktree_dir),
+    };
+    if nested {
+        return Err(WtError::NestedWorktree(worktree_dir));
     }
+    Ok(())
+}
 
-    let git_dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-    if let Some(pos) = git_dir.find("/.git/worktrees/") {
-        let worktree_name = &git_dir[pos + "/.git/worktrees/".len()..];
-        Ok(worktree_name.to_string())
-    } else {
-        Ok("main".to_string())
+/// Name of the worktree containing `path`, or `"main"` outside a linked
+/// worktree (the main checkout, a submodule, a bare repo). A linked
+/// worktree is one whose git dir differs from the shared common dir; its
+/// name is read back from its top-level directory, which `create_worktree`
+/// named after the branch.
+pub fn get_current_worktree_name(path: &Path) -> Result<String> {
+    let git = Git::new(path);
+    let not_a_repo = |_| WtError::NotAGitRepo(path.to_path_buf());
+    let dirs = git
+        .rev_parse(&["--path-format=absolute", "--git-dir", "--git-common-dir"])
+        .map_err(not_a_repo)?;
+    let mut dirs = dirs.lines().map(|dir| canonical_or_lexical(Path::new(dir)));
+    if dirs.next() == dirs.next() {
+        return Ok("main".to_string());
     }
+
+    let toplevel = git.show_toplevel().map_err(not_a_repo)?;
+    let dir_name = toplevel.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    Ok(unsanitize_from_path(dir_name))
 }
 
 #[derive(Debug, Clone)]
@@ -143,18 +409,67 @@ pub struct WorktreeInfo {
     pub task_id: String,
     pub path: PathBuf,
     pub branch: String,
+    /// Whether the worktree has uncommitted changes. Only filled in by the
+    /// `*_with_status` listings (`wt ls --plain --status`); `None` otherwise
+    /// or if git failed.
+    pub dirty: Option<bool>,
+    /// Commits `(ahead, behind)` the branch's upstream. Only filled in by
+    /// the `*_with_status` listings; `None` when there is no upstream.
+    pub ahead_behind: Option<(u32, u32)>,
+}
+
+fn ahead_behind(worktree_path: &Path) -> Option<(u32, u32)> {
+    Git::new(worktree_path).ahead_behind("@{upstream}").ok()
+}
+
+/// Fill in the dirty flag and ahead/behind counts of `worktrees`. The
+/// per-worktree git calls fan out on a small pool of scoped threads, since
+/// `git status` walks a full working tree.
+fn fill_status(worktrees: &mut [WorktreeInfo]) {
+    let chunk_size = worktrees.len().div_ceil(STATUS_WORKERS).max(1);
+
+    std::thread::scope(|scope| {
+        for chunk in worktrees.chunks_mut(chunk_size) {
+            scope.spawn(move || {
+                for worktree in chunk {
+                    worktree.dirty = Git::new(&worktree.path).is_dirty().ok();
+                    worktree.ahead_behind = ahead_behind(&worktree.path);
+                }
+            });
+        }
+    });
+}
+
+/// Reject names git would refuse as a branch (spaces, a leading `-`, `..`,
+/// a `.lock` suffix, ...) before anything is created, so the user gets a
+/// clear message instead of a `git worktree add` failure.
+pub fn validate_branch_name(name: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(["check-ref-format", "--branch", name])
+        .run()
+        .map_err(|e| WtError::io("Failed to execute git check-ref-format", e))?;
+
+    if !output.status.success() {
+        return Err(WtError::InvalidBranchName(name.to_string()));
+    }
+
+    Ok(())
 }
 
 pub struct WorktreeManager {
     repo_path: PathBuf,
+    git: Git,
 }
 
 impl WorktreeManager {
     pub fn new(repo_path: PathBuf) -> Result<Self> {
         if !repo_path.join(".git").exists() {
-            anyhow::bail!("Not a git repository: {:?}", repo_path);
+            return Err(WtError::NotAGitRepo(repo_path));
         }
-        Ok(Self { repo_path })
+        Ok(Self {
+            git: Git::new(&repo_path),
+            repo_path,
+        })
     }
 
     pub fn create_worktree(
@@ -162,130 +477,149 @@ impl WorktreeManager {
         task_id: &str,
         base_branch: &str,
         worktree_dir: &Path,
-        select_remote_branch: impl FnOnce(&[String]) -> Result<String>,
+        select_remote_branch: impl FnOnce(&[String]) -> Option<String>,
     ) -> Result<PathBuf> {
-        // Sanitize for filesystem (/ -> --) but keep original for git
-        let safe_name = sanitize_for_path(task_id);
-        let worktree_path = worktree_dir.join(&safe_name);
+        validate_branch_name(task_id)?;
+
+        // Sanitize for filesystem but keep original for git
+        let worktree_path = worktree_path_for(worktree_dir, task_id);
 
         if worktree_path.exists() {
-            anyhow::bail!("Worktree path already exists: {:?}", worktree_path);
+            return Err(WtError::WorktreeExists(worktree_path));
         }
 
+        // Where a new branch starts; `None` checks out the existing local branch
         let mut upstream_branch: Option<String> = None;
-        let output = if self.local_branch_exists(task_id) {
-            // Local branch exists, just check it out
-            Command::new("git")
-                .args(["worktree", "add"])
-                .arg(&worktree_path)
-                .arg(task_id)
-                .current_dir(&self.repo_path)
-                .output()
-                .context("Failed to execute git worktree add")?
+        let start_point = if self.local_branch_exists(task_id) {
+            None
         } else {
             let remote_branches = self.remote_branch_candidates(task_id)?;
             match remote_branches.as_slice() {
-                [] => Command::new("git")
-                    .args(["worktree", "add", "-b", task_id])
-                    .arg(&worktree_path)
-                    .arg(base_branch)
-                    .current_dir(&self.repo_path)
-                    .output()
-                    .context("Failed to execute git worktree add")?,
+                [] => Some(base_branch.to_string()),
                 [remote_branch] => {
                     upstream_branch = Some(remote_branch.clone());
-                    Command::new("git")
-                        .args(["worktree", "add", "-b", task_id])
-                        .arg(&worktree_path)
-                        .arg(remote_branch)
-                        .current_dir(&self.repo_path)
-                        .output()
-                        .context("Failed to execute git worktree add")?
+                    upstream_branch.clone()
                 }
-
                 _ => {
-                    let remote_branch = select_remote_branch(&remote_branches)?;
+                    let remote_branch = select_remote_branch(&remote_branches)
+                        .ok_or_else(|| WtError::RemoteBranchNotSelected(task_id.to_string()))?;
                     upstream_branch = Some(remote_branch.clone());
-                    Command::new("git")
-                        .args(["worktree", "add", "-b", task_id])
-                        .arg(&worktree_path)
-                        .arg(&remote_branch)
-                        .current_dir(&self.repo_path)
-                        .output()
-                        .context("Failed to execute git worktree add")?
+                    Some(remote_branch)
                 }
             }
         };
 
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to create worktree: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
+        if start_point.is_none() {
+            if let Some(existing) = self
+                .list_worktrees()?
+                .into_iter()
+                .find(|wt| wt.branch == task_id)
+            {
+                return Err(WtError::BranchCheckedOut {
+                    branch: task_id.to_string(),
+                    main_checkout: existing.task_id.is_empty(),
+                    path: existing.path,
+                });
+            }
         }
 
+        let mut args: Vec<&OsStr> = vec!["worktree".as_ref(), "add".as_ref()];
+        match &start_point {
+            None => args.extend([worktree_path.as_os_str(), task_id.as_ref()]),
+            Some(start) => args.extend([
+                "-b".as_ref(),
+                task_id.as_ref(),
+                worktree_path.as_os_str(),
+                start.as_ref(),
+            ]),
+        }
+        self.git.run(args)?;
+
         if let Some(remote_branch) = upstream_branch {
             if let Some(remote_name) = remote_branch.split('/').next() {
                 if self.remote_exists(remote_name) {
-                    let output = Command::new("git")
-                        .args(["branch", "--set-upstream-to", &remote_branch, task_id])
-                        .current_dir(&self.repo_path)
-                        .output()
-                        .context("Failed to set branch upstream")?;
-
-                    if !output.status.success() {
-                        anyhow::bail!(
-                            "Failed to set branch upstream: {}",
-                            String::from_utf8_lossy(&output.stderr)
-                        );
-                    }
+                    self.git
+                        .run(["branch", "--set-upstream-to", &remote_branch, task_id])?;
                 }
             }
         }
 
+        self.finish_worktree(&worktree_path);
+        Ok(worktree_path)
+    }
+
+    /// Create a worktree on a new local branch `task_id` that tracks
+    /// `remote_ref` (e.g. `origin/feature`), so `git pull` works right away.
+    pub fn create_tracking_worktree(
+        &self,
+        task_id: &str,
+        remote_ref: &str,
+        worktree_dir: &Path,
+    ) -> Result<PathBuf> {
+        validate_branch_name(task_id)?;
+
+        let worktree_path = worktree_path_for(worktree_dir, task_id);
+        if worktree_path.exists() {
+            return Err(WtError::WorktreeExists(worktree_path));
+        }
+
+        if !self.git.succeeds([
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/remotes/{}", remote_ref),
+        ]) {
+            return Err(WtError::RemoteBranchNotFound(remote_ref.to_string()));
+        }
+
+        self.git.run([
+            "worktree".as_ref(),
+            "add".as_ref(),
+            "--track".as_ref(),
+            "-b".as_ref(),
+            task_id.as_ref(),
+            worktree_path.as_os_str(),
+            remote_ref.as_ref(),
+        ])?;
+
+        self.finish_worktree(&worktree_path);
+        Ok(worktree_path)
+    }
+
+    /// Setup shared by every new worktree once `git worktree add` succeeded.
+    fn finish_worktree(&self, worktree_path: &Path) {
         // Set up autoSetupRemote so `git push` works without -u origin HEAD
         // (avoids "upstream is gone" warning before first push)
-        Command::new("git")
-            .args(["config", "push.autoSetupRemote", "true"])
-            .current_dir(&worktree_path)
-            .output()
+        Git::new(worktree_path)
+            .run(["config", "push.autoSetupRemote", "true"])
             .ok();
 
         // Symlink files from `# wt copy` section in .gitignore
-        symlink_wt_copy_files(&self.repo_path, &worktree_path);
-
-        Ok(worktree_path)
+        let copy_summary = symlink_wt_copy_files(&self.repo_path, worktree_path);
+        if let Some(line) = copy_summary.summary_line() {
+            eprintln!("wt: {}", line);
+        }
+        for conflict in &copy_summary.conflicts {
+            eprintln!(
+                "wt: warning: not linking '{}': a different file already exists in the worktree",
+                conflict.display()
+            );
+        }
     }
 
     fn local_branch_exists(&self, branch: &str) -> bool {
-        Command::new("git")
-            .args([
-                "show-ref",
-                "--verify",
-                "--quiet",
-                &format!("refs/heads/{}", branch),
-            ])
-            .current_dir(&self.repo_path)
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
+        self.git.succeeds([
+            "show-ref",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", branch),
+        ])
     }
 
     fn remote_branch_candidates(&self, branch: &str) -> Result<Vec<String>> {
-        let output = Command::new("git")
-            .args(["for-each-ref", "--format=%(refname:short)", "refs/remotes"])
-            .current_dir(&self.repo_path)
-            .output()
-            .context("Failed to execute git for-each-ref")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to list remote branches: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
-
-        let mut candidates: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        let mut candidates: Vec<String> = self
+            .git
+            .run(["for-each-ref", "--format=%(refname:short)", "refs/remotes"])?
             .lines()
             .map(str::trim)
             .filter(|refname| !refname.is_empty() && !refname.ends_with("/HEAD"))
@@ -303,29 +637,12 @@ impl WorktreeManager {
     }
 
     fn remote_exists(&self, remote: &str) -> bool {
-        Command::new("git")
-            .args(["config", "--get", &format!("remote.{}.url", remote)])
-            .current_dir(&self.repo_path)
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
+        self.git
+            .succeeds(["config", "--get", &format!("remote.{}.url", remote)])
     }
 
     pub fn list_worktrees(&self) -> Result<Vec<WorktreeInfo>> {
-        let output = Command::new("git")
-            .args(["worktree", "list", "--porcelain"])
-            .current_dir(&self.repo_path)
-            .output()
-            .context("Failed to execute git worktree list")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "Failed to list worktrees: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stdout = self.git.worktree_list()?;
         let mut worktrees = Vec::new();
         let mut current_worktree: Option<(PathBuf, Option<String>)> = None;
 
@@ -354,12 +671,44 @@ impl WorktreeManager {
         Ok(worktrees)
     }
 
+    /// The worktrees wt manages: those directly inside `worktree_dir`. The
+    /// main checkout and worktrees made by hand elsewhere are left out, so
+    /// commands like `rm --all` never act on them.
+    pub fn list_managed(&self, worktree_dir: &Path) -> Result<Vec<WorktreeInfo>> {
+        let managed_dir = canonical_or_lexical(worktree_dir);
+        Ok(self
+            .list_worktrees()?
+            .into_iter()
+            .filter(|wt| !wt.task_id.is_empty())
+            .filter(|wt| wt.path.parent().map(canonical_or_lexical).as_ref() == Some(&managed_dir))
+            .collect())
+    }
+
+    /// Like `list_worktrees`, but also fills in each worktree's dirty flag
+    /// and ahead/behind counts (see `fill_status`).
+    pub fn list_worktrees_with_status(&self) -> Result<Vec<WorktreeInfo>> {
+        let mut worktrees = self.list_worktrees()?;
+        fill_status(&mut worktrees);
+        Ok(worktrees)
+    }
+
+    /// `list_managed` with status, as `list_worktrees_with_status`.
+    pub fn list_managed_with_status(&self, worktree_dir: &Path) -> Result<Vec<WorktreeInfo>> {
+        let mut worktrees = self.list_managed(worktree_dir)?;
+        fill_status(&mut worktrees);
+        Ok(worktrees)
+    }
+
     fn parse_worktree_entry(&self, path: PathBuf, branch: Option<String>) -> WorktreeInfo {
         let task_id = if path == self.repo_path {
             String::new()
+        } else if let Some(branch) = &branch {
+            // The branch git reports is the real name; the directory name is
+            // a lossy encoding of it (`feat--x` and `feat/x` collide).
+            branch.clone()
         } else {
+            // Detached HEAD: fall back to the directory name (-- -> /)
             let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            // Convert filesystem name back to original (-- -> /)
             unsanitize_from_path(dir_name)
         };
 
@@ -367,6 +716,8 @@ impl WorktreeManager {
             task_id,
             path,
             branch: branch.unwrap_or_default(),
+            dirty: None,
+            ahead_behind: None,
         }
     }
 
@@ -374,44 +725,42 @@ impl WorktreeManager {
         // Look up the actual path from git
         let wt_info = self
             .get_worktree_info(task_id)?
-            .ok_or_else(|| anyhow::anyhow!("Worktree '{}' not found", task_id))?;
+            .ok_or_else(|| WtError::WorktreeNotFound(task_id.to_string()))?;
 
         // If path doesn't exist on disk, just prune stale entries
         if !wt_info.path.exists() {
-            Command::new("git")
-                .args(["worktree", "prune"])
-                .current_dir(&self.repo_path)
-                .output()
-                .context("Failed to prune stale worktrees")?;
+            self.git.run(["worktree", "prune"])?;
             return Ok(());
         }
 
-        let output = Command::new("git")
-            .args(["worktree", "remove"])
-            .arg(&wt_info.path)
-            .current_dir(&self.repo_path)
-            .output()
-            .context("Failed to execute git worktree remove")?;
-
-        if !output.status.success() {
-            let output_force = Command::new("git")
-                .args(["worktree", "remove", "--force"])
-                .arg(&wt_info.path)
-                .current_dir(&self.repo_path)
-                .output()
-                .context("Failed to execute git worktree remove --force")?;
-
-            if !output_force.status.success() {
-                anyhow::bail!(
-                    "Failed to remove worktree: {}",
-                    String::from_utf8_lossy(&output_force.stderr)
-                );
-            }
+        let path = wt_info.path.as_os_str();
+        if self
+            .git
+            .run(["worktree".as_ref(), "remove".as_ref(), path])
+            .is_err()
+        {
+            self.git.run([
+                "worktree".as_ref(),
+                "remove".as_ref(),
+                "--force".as_ref(),
+                path,
+            ])?;
         }
 
         Ok(())
     }
 
+    /// Count commits on `branch` that are not reachable from `base`.
+    pub fn unmerged_commit_count(&self, branch: &str, base: &str) -> Result<u32> {
+        let output = self
+            .git
+            .run(["rev-list", "--count", &format!("{}..{}", base, branch)])?;
+        output.parse().map_err(|_| WtError::UnexpectedOutput {
+            command: "git rev-list --count".to_string(),
+            output,
+        })
+    }
+
     pub fn worktree_exists(&self, task_id: &str) -> bool {
         self.get_worktree_info(task_id)
             .map(|info| info.is_some())
@@ -435,19 +784,19 @@ mod tests {
         let repo_path = temp_dir.path();
 
         Command::new("git")
-            .args(&["init", "-b", "main"])
+            .args(["init", "-b", "main"])
             .current_dir(repo_path)
             .output()
             .unwrap();
 
         Command::new("git")
-            .args(&["config", "user.email", "test@example.com"])
+            .args(["config", "user.email", "test@example.com"])
             .current_dir(repo_path)
             .output()
             .unwrap();
 
         Command::new("git")
-            .args(&["config", "user.name", "Test User"])
+            .args(["config", "user.name", "Test User"])
             .current_dir(repo_path)
             .output()
             .unwrap();
@@ -455,13 +804,13 @@ mod tests {
         fs::write(repo_path.join("README.md"), "# Test Repo\n").unwrap();
 
         Command::new("git")
-            .args(&["add", "."])
+            .args(["add", "."])
             .current_dir(repo_path)
             .output()
             .unwrap();
 
         Command::new("git")
-            .args(&["commit", "-m", "Initial commit"])
+            .args(["commit", "-m", "Initial commit"])
             .current_dir(repo_path)
             .output()
             .unwrap();
@@ -469,6 +818,132 @@ mod tests {
         temp_dir
     }
 
+    #[test]
+    fn test_sanitize_for_path_keeps_simple_names_readable() {
+        assert_eq!(sanitize_for_path("feat/x"), "feat--x");
+        assert_eq!(sanitize_for_path("fix-auth-bug"), "fix-auth-bug");
+        assert_eq!(sanitize_for_path("foo--bar"), "foo%2D%2Dbar");
+        assert_eq!(sanitize_for_path("a-/b"), "a%2D--b");
+        assert_eq!(sanitize_for_path("100%"), "100%25");
+        assert_eq!(unsanitize_from_path("feat--x"), "feat/x");
+        assert_eq!(unsanitize_from_path("50%zz"), "50%zz");
+    }
+
+    #[test]
+    fn test_sanitize_round_trips_every_short_name() {
+        // Exhaustive over an alphabet covering every special case
+        let alphabet = ['a', '-', '/', '%', '2', 'D', 'é'];
+        let mut names = vec![String::new()];
+        for _ in 0..5 {
+            names = names
+                .iter()
+                .flat_map(|name| alphabet.iter().map(move |c| format!("{}{}", name, c)))
+                .collect();
+            for name in &names {
+                let encoded = sanitize_for_path(name);
+                assert!(!encoded.contains('/'), "{:?} -> {:?}", name, encoded);
+                assert_eq!(&unsanitize_from_path(&encoded), name, "via {:?}", encoded);
+            }
+        }
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Fix auth bug"), "fix-auth-bug");
+        assert_eq!(slugify("  Fix: auth/login bug!! "), "fix-auth-login-bug");
+        assert_eq!(slugify("Use `--dir` in .wt.toml"), "use-dir-in-wt-toml");
+        assert_eq!(slugify("Ünïcode only ✨"), "n-code-only");
+        assert_eq!(slugify("!!!"), "");
+        assert!(slugify(&"word ".repeat(30)).len() <= MAX_SLUG_LEN);
+        assert!(!slugify(&"word ".repeat(30)).ends_with('-'));
+    }
+
+    #[test]
+    fn test_issue_branch_name() {
+        assert_eq!(
+            issue_branch_name(123, Some("Fix auth bug")),
+            "123-fix-auth-bug"
+        );
+        assert_eq!(issue_branch_name(123, None), "issue-123");
+        assert_eq!(issue_branch_name(123, Some("???")), "issue-123");
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("spike/*", "spike/a"));
+        assert!(glob_match("spike/*", "spike/a/b"));
+        assert!(!glob_match("spike/*", "feature/c"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("fix-?", "fix-1"));
+        assert!(!glob_match("fix-?", "fix-12"));
+        assert!(glob_match("*-auth", "feature-auth"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+    }
+
+    #[test]
+    fn test_worktree_dir_pattern() {
+        let repo = Path::new("/home/me/repo");
+        assert_eq!(
+            worktree_dir_pattern(repo, &repo.join(".worktrees")),
+            Some(".worktrees".to_string())
+        );
+        assert_eq!(
+            worktree_dir_pattern(repo, &repo.join("build/./trees")),
+            Some("build/trees".to_string())
+        );
+        assert_eq!(worktree_dir_pattern(repo, &repo.join("../trees")), None);
+        assert_eq!(worktree_dir_pattern(repo, Path::new("/tmp/trees")), None);
+        assert_eq!(worktree_dir_pattern(repo, repo), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_wt_copy_files_does_not_clobber() {
+        let repo = TempDir::new().unwrap();
+        let worktree = TempDir::new().unwrap();
+
+        fs::write(
+            repo.path().join(".gitignore"),
+            "# wt copy\n.env\n.env.local\n.secrets\n.missing\n",
+        )
+        .unwrap();
+        for name in [".env", ".env.local", ".secrets"] {
+            fs::write(repo.path().join(name), "repo").unwrap();
+        }
+
+        // A real file the worktree already has
+        fs::write(worktree.path().join(".env"), "worktree").unwrap();
+        // A correct link from an earlier run
+        std::os::unix::fs::symlink(
+            repo.path().join(".env.local"),
+            worktree.path().join(".env.local"),
+        )
+        .unwrap();
+        // A stale link pointing somewhere else
+        std::os::unix::fs::symlink("/nonexistent", worktree.path().join(".secrets")).unwrap();
+
+        let summary = symlink_wt_copy_files(repo.path(), worktree.path());
+
+        assert_eq!(summary.conflicts, vec![PathBuf::from(".env")]);
+        assert_eq!(summary.skipped, vec![PathBuf::from(".env.local")]);
+        assert_eq!(summary.linked, vec![PathBuf::from(".secrets")]);
+        assert_eq!(
+            summary.summary_line().unwrap(),
+            "linked 1 '# wt copy' path(s): .secrets (1 already linked)"
+        );
+        assert_eq!(
+            fs::read_to_string(worktree.path().join(".env")).unwrap(),
+            "worktree"
+        );
+        assert_eq!(
+            fs::read_link(worktree.path().join(".secrets")).unwrap(),
+            repo.path().join(".secrets")
+        );
+    }
+
     #[test]
     fn test_create_worktree() {
         let repo = setup_git_repo();
@@ -488,6 +963,98 @@ mod tests {
         assert!(worktree_path.join("README.md").exists());
     }
 
+    #[test]
+    fn test_create_worktree_rejects_invalid_branch_name() {
+        let repo = setup_git_repo();
+        let worktree_dir = TempDir::new().unwrap();
+
+        let manager = WorktreeManager::new(repo.path().to_path_buf()).unwrap();
+        for name in ["feat branch", "-x", "a..b", "x.lock"] {
+            let error = manager
+                .create_worktree(name, "main", worktree_dir.path(), |_| unreachable!())
+                .unwrap_err();
+            assert!(
+                matches!(&error, WtError::InvalidBranchName(n) if n == name),
+                "{}: {}",
+                name,
+                error
+            );
+            assert!(error.to_string().contains("not a valid git branch name"));
+        }
+        assert_eq!(fs::read_dir(worktree_dir.path()).unwrap().count(), 0);
+        assert!(validate_branch_name("feat/x").is_ok());
+    }
+
+    #[test]
+    fn test_list_worktrees_with_status() {
+        let repo = setup_git_repo();
+        let worktree_dir = TempDir::new().unwrap();
+
+        let manager = WorktreeManager::new(repo.path().to_path_buf()).unwrap();
+        let names: Vec<String> = (0..10).map(|i| format!("feature-{}", i)).collect();
+        for name in &names {
+            let path = manager
+                .create_worktree(name, "main", worktree_dir.path(), |_| unreachable!())
+                .unwrap();
+            if name.ends_with(['1', '4', '7']) {
+                fs::write(path.join("scratch.txt"), "wip").unwrap();
+            }
+            if name.ends_with(['2', '7']) {
+                // Track main and commit once so the branch is 1 ahead
+                let git = |args: &[&str]| {
+                    let output = Command::new("git")
+                        .args(args)
+                        .current_dir(&path)
+                        .output()
+                        .unwrap();
+                    assert!(output.status.success(), "{:?}", output);
+                };
+                git(&["branch", "--set-upstream-to", "main"]);
+                git(&["commit", "--allow-empty", "-m", "wip"]);
+            }
+        }
+
+        let worktrees = manager.list_worktrees_with_status().unwrap();
+        assert_eq!(worktrees.len(), names.len() + 1);
+        for worktree in &worktrees {
+            let dirty = worktree.task_id.ends_with(['1', '4', '7']);
+            assert_eq!(worktree.dirty, Some(dirty), "{}", worktree.task_id);
+
+            let tracked = worktree.task_id.ends_with(['2', '7']);
+            let expected = tracked.then_some((1, 0));
+            assert_eq!(worktree.ahead_behind, expected, "{}", worktree.task_id);
+        }
+
+        assert!(manager
+            .list_worktrees()
+            .unwrap()
+            .iter()
+            .all(|worktree| worktree.dirty.is_none()));
+    }
+
+    #[test]
+    fn test_list_managed_skips_hand_made_worktrees() {
+        let repo = setup_git_repo();
+        let worktree_dir = TempDir::new().unwrap();
+        let elsewhere = TempDir::new().unwrap();
+
+        let manager = WorktreeManager::new(repo.path().to_path_buf()).unwrap();
+        manager
+            .create_worktree("managed", "main", worktree_dir.path(), |_| unreachable!())
+            .unwrap();
+        Command::new("git")
+            .args(["worktree", "add", "-b", "by-hand"])
+            .arg(elsewhere.path().join("by-hand"))
+            .current_dir(repo.path())
+            .output()
+            .unwrap();
+
+        assert_eq!(manager.list_worktrees().unwrap().len(), 3);
+        let managed = manager.list_managed(worktree_dir.path()).unwrap();
+        let names: Vec<_> = managed.iter().map(|wt| wt.task_id.as_str()).collect();
+        assert_eq!(names, vec!["managed"]);
+    }
+
     #[test]
     fn test_list_worktrees() {
         let repo = setup_git_repo();
@@ -513,6 +1080,68 @@ mod tests {
         assert!(task_ids.contains(&"feature-2".to_string()));
     }
 
+    #[test]
+    fn test_ensure_git_excluded_is_shared_and_idempotent() {
+        let repo = setup_git_repo();
+        let worktree_dir = TempDir::new().unwrap();
+        let manager = WorktreeManager::new(repo.path().to_path_buf()).unwrap();
+        let worktree_path = manager
+            .create_worktree("logged", "main", worktree_dir.path(), |_| unreachable!())
+            .unwrap();
+
+        ensure_git_excluded(&worktree_path, ".wt-agent.log").unwrap();
+        ensure_git_excluded(repo.path(), ".wt-agent.log").unwrap();
+
+        let exclude = fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap();
+        assert_eq!(exclude.matches(".wt-agent.log").count(), 1);
+
+        fs::write(worktree_path.join(".wt-agent.log"), "output\n").unwrap();
+        let status = Git::new(&worktree_path)
+            .run(["status", "--porcelain"])
+            .unwrap();
+        assert_eq!(status, "");
+    }
+
+    #[test]
+    fn test_create_worktree_for_checked_out_branch_names_existing_worktree() {
+        let repo = setup_git_repo();
+        let first_dir = TempDir::new().unwrap();
+        let second_dir = TempDir::new().unwrap();
+        let manager = WorktreeManager::new(repo.path().to_path_buf()).unwrap();
+
+        let first = manager
+            .create_worktree("feature/dup", "main", first_dir.path(), |_| unreachable!())
+            .unwrap();
+        let error = manager
+            .create_worktree("feature/dup", "main", second_dir.path(), |_| unreachable!())
+            .unwrap_err();
+
+        match &error {
+            WtError::BranchCheckedOut {
+                branch,
+                path,
+                main_checkout,
+            } => {
+                assert_eq!(branch, "feature/dup");
+                assert_eq!(path.canonicalize().unwrap(), first.canonicalize().unwrap());
+                assert!(!main_checkout);
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        assert!(error.to_string().contains("wt use feature/dup"));
+
+        let error = manager
+            .create_worktree("main", "main", second_dir.path(), |_| unreachable!())
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            WtError::BranchCheckedOut {
+                main_checkout: true,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_remove_worktree() {
         let repo = setup_git_repo();
@@ -600,7 +1229,7 @@ mod tests {
             worktree_dir.path(),
             |_| unreachable!(),
         );
-        assert!(result.is_err());
+        assert!(matches!(result, Err(WtError::WorktreeExists(_))));
     }
 
     #[test]
@@ -609,7 +1238,7 @@ mod tests {
 
         let manager = WorktreeManager::new(repo.path().to_path_buf()).unwrap();
         let result = manager.remove_worktree("nonexistent");
-        assert!(result.is_err());
+        assert!(matches!(result, Err(WtError::WorktreeNotFound(_))));
     }
 
     #[test]
@@ -624,7 +1253,7 @@ mod tests {
             worktree_dir.path(),
             |_| unreachable!(),
         );
-        assert!(result.is_err());
+        assert!(matches!(result, Err(WtError::Git { .. })));
     }
 
     #[test]
@@ -701,6 +1330,49 @@ mod tests {
         assert_eq!(branch.trim(), "remote-feature");
     }
 
+    #[test]
+    fn test_create_tracking_worktree_sets_upstream() {
+        let repo = setup_git_repo();
+        let worktree_dir = TempDir::new().unwrap();
+
+        let head = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(repo.path())
+            .output()
+            .unwrap();
+        let commit = String::from_utf8_lossy(&head.stdout).trim().to_string();
+
+        Command::new("git")
+            .args(["remote", "add", "origin", "https://example.com/repo.git"])
+            .current_dir(repo.path())
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["update-ref", "refs/remotes/origin/feature", &commit])
+            .current_dir(repo.path())
+            .output()
+            .unwrap();
+
+        let manager = WorktreeManager::new(repo.path().to_path_buf()).unwrap();
+        let result =
+            manager.create_tracking_worktree("local", "origin/missing", worktree_dir.path());
+        assert!(matches!(result, Err(WtError::RemoteBranchNotFound(_))));
+
+        let worktree_path = manager
+            .create_tracking_worktree("local", "origin/feature", worktree_dir.path())
+            .unwrap();
+
+        let output = Command::new("git")
+            .args(["rev-parse", "--abbrev-ref", "HEAD@{upstream}"])
+            .current_dir(&worktree_path)
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            "origin/feature"
+        );
+    }
+
     #[test]
     fn test_create_worktree_prompts_for_ambiguous_remote_branch() {
         let repo = setup_git_repo();
@@ -733,7 +1405,7 @@ mod tests {
         let worktree_path = manager
             .create_worktree("shared-feature", "main", worktree_dir.path(), |remotes| {
                 seen_candidates = remotes.to_vec();
-                Ok(remotes[1].clone())
+                Some(remotes[1].clone())
             })
             .unwrap();
 
@@ -754,6 +1426,51 @@ mod tests {
         assert_eq!(branch.trim(), "shared-feature");
     }
 
+    #[test]
+    fn test_branch_name_with_double_dash() {
+        let repo = setup_git_repo();
+        let worktree_dir = TempDir::new().unwrap();
+
+        let manager = WorktreeManager::new(repo.path().to_path_buf()).unwrap();
+        let worktree_path = manager
+            .create_worktree("feat--x", "main", worktree_dir.path(), |_| unreachable!())
+            .unwrap();
+
+        let worktrees = manager.list_worktrees().unwrap();
+        assert!(worktrees.iter().any(|w| w.task_id == "feat--x"));
+        assert!(!worktrees.iter().any(|w| w.task_id == "feat/x"));
+
+        let info = manager.get_worktree_info("feat--x").unwrap().unwrap();
+        assert_eq!(info.branch, "feat--x");
+
+        manager.remove_worktree("feat--x").unwrap();
+        assert!(!worktree_path.exists());
+    }
+
+    #[test]
+    fn test_detached_worktree_falls_back_to_directory_name() {
+        let repo = setup_git_repo();
+        let worktree_dir = TempDir::new().unwrap();
+        let worktree_path = worktree_dir.path().join("spike--detached");
+
+        let output = Command::new("git")
+            .args(["worktree", "add", "--detach"])
+            .arg(&worktree_path)
+            .arg("main")
+            .current_dir(repo.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let manager = WorktreeManager::new(repo.path().to_path_buf()).unwrap();
+        let info = manager
+            .get_worktree_info("spike/detached")
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.path, worktree_path);
+        assert!(info.branch.is_empty());
+    }
+
     #[test]
     fn test_branch_name_with_slashes() {
         let repo = setup_git_repo();
@@ -0,0 +1,41 @@
+use std::process::Command;
+
+mod common;
+use common::{setup_git_repo, wt};
+
+#[test]
+fn test_diff_shows_only_branch_changes() {
+    let repo = setup_git_repo();
+    let output = wt(repo.path(), &["new", "feature", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let worktree = std::path::PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+
+    std::fs::write(worktree.join("feature.txt"), "feature\n").unwrap();
+    std::fs::write(repo.path().join("main.txt"), "main\n").unwrap();
+    for (dir, message) in [(worktree.as_path(), "feature"), (repo.path(), "main")] {
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", message])
+            .current_dir(dir)
+            .output()
+            .unwrap();
+    }
+
+    let output = wt(repo.path(), &["diff", "feature"]);
+    assert!(output.status.success(), "wt diff failed: {:?}", output);
+    let diff = String::from_utf8_lossy(&output.stdout);
+    assert!(diff.contains("+feature"));
+    assert!(!diff.contains("main.txt"));
+
+    let output = wt(repo.path(), &["diff", "feature", "--stat"]);
+    assert!(
+        output.status.success(),
+        "wt diff --stat failed: {:?}",
+        output
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1 file changed"));
+}
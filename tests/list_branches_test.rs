@@ -0,0 +1,63 @@
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+mod common;
+use common::{setup_git_repo, wt};
+
+fn git(repo_path: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn stdout_lines(output: &Output) -> Vec<String> {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_list_branches_skips_checked_out_branches() {
+    let repo = setup_git_repo();
+    git(repo.path(), &["branch", "feature/a"]);
+    git(repo.path(), &["branch", "feature/b"]);
+
+    let output = wt(repo.path(), &["new", "feature/a", "--print-path"]);
+    assert!(output.status.success());
+
+    let output = wt(repo.path(), &["list-branches"]);
+    assert_eq!(stdout_lines(&output), vec!["feature/b"]);
+}
+
+#[test]
+fn test_list_branches_remote_skips_local_and_checked_out() {
+    let origin = setup_git_repo();
+    git(origin.path(), &["branch", "shared"]);
+    git(origin.path(), &["branch", "remote-only"]);
+
+    let clone = TempDir::new().unwrap();
+    let work = clone.path().join("work");
+    git(
+        clone.path(),
+        &["clone", "-q", origin.path().to_str().unwrap(), "work"],
+    );
+    git(&work, &["branch", "shared", "origin/shared"]);
+
+    let output = wt(&work, &["list-branches", "--remote"]);
+    assert_eq!(stdout_lines(&output), vec!["shared", "origin/remote-only"]);
+}
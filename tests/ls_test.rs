@@ -0,0 +1,52 @@
+use std::process::Command;
+
+mod common;
+use common::{setup_git_repo, wt};
+
+#[test]
+fn test_ls_plain_prints_names() {
+    let repo = setup_git_repo();
+    for name in ["feature/a", "feature-b"] {
+        let output = wt(repo.path(), &["new", name, "--print-path"]);
+        assert!(output.status.success(), "wt new failed: {:?}", output);
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+        .args(["ls", "--plain", "--marker"])
+        .current_dir(repo.path())
+        .env("WT_NAME", "feature-b")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "wt ls failed: {:?}", output);
+    let mut lines: Vec<_> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    lines.sort();
+    assert_eq!(lines, vec!["feature-b *", "feature/a"]);
+
+    let output = wt(repo.path(), &["ls", "--plain"]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains('*'));
+}
+
+#[test]
+fn test_ls_plain_status_columns() {
+    let repo = setup_git_repo();
+    let output = wt(repo.path(), &["new", "feature-a", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let output = wt(repo.path(), &["ls", "--plain", "--status"]);
+    assert!(output.status.success(), "wt ls failed: {:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "feature-a\tclean\t-\n"
+    );
+
+    std::fs::write(std::path::Path::new(&path).join("new.txt"), "x").unwrap();
+    let output = wt(repo.path(), &["ls", "--plain", "--status"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "feature-a\tdirty\t-\n"
+    );
+}
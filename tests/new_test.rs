@@ -0,0 +1,243 @@
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+use common::{setup_git_repo, wt};
+
+fn git(repo_path: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn test_new_migration_aborts_on_untracked_collision() {
+    let repo = setup_git_repo();
+    std::fs::write(repo.path().join("notes.txt"), "from main\n").unwrap();
+    git(repo.path(), &["add", "notes.txt"]);
+    git(repo.path(), &["commit", "-m", "Add notes"]);
+
+    git(repo.path(), &["checkout", "-b", "feature"]);
+    git(repo.path(), &["rm", "-q", "notes.txt"]);
+    git(repo.path(), &["commit", "-m", "Drop notes"]);
+
+    // An untracked notes.txt would be clobbered by checking out main,
+    // alongside a tracked change that migration would stash
+    std::fs::write(repo.path().join("notes.txt"), "my notes\n").unwrap();
+    std::fs::write(repo.path().join("README.md"), "# Edited\n").unwrap();
+
+    let output = wt(repo.path(), &["new", "--print-path"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("notes.txt"), "{}", stderr);
+    assert!(stderr.contains("nothing was changed"), "{}", stderr);
+
+    // Still on feature with both changes in place and nothing stashed
+    assert_eq!(git(repo.path(), &["branch", "--show-current"]), "feature");
+    assert_eq!(git(repo.path(), &["stash", "list"]), "");
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("notes.txt")).unwrap(),
+        "my notes\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("README.md")).unwrap(),
+        "# Edited\n"
+    );
+    assert!(!repo.path().join(".worktrees/feature").exists());
+}
+
+#[test]
+fn test_new_migrates_changes_into_worktree() {
+    let repo = setup_git_repo();
+    git(repo.path(), &["checkout", "-b", "feature"]);
+    std::fs::write(repo.path().join("README.md"), "# Edited\n").unwrap();
+
+    let output = wt(repo.path(), &["new", "--print-path"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    assert_eq!(git(repo.path(), &["branch", "--show-current"]), "main");
+    assert_eq!(
+        std::fs::read_to_string(Path::new(&path).join("README.md")).unwrap(),
+        "# Edited\n"
+    );
+    assert_eq!(git(repo.path(), &["stash", "list"]), "");
+}
+
+#[test]
+fn test_new_branches_from_default_branch_without_b() {
+    let repo = setup_git_repo();
+    git(repo.path(), &["branch", "-m", "main", "master"]);
+
+    let output = wt(repo.path(), &["new", "feature", "--print-path"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert_eq!(
+        git(Path::new(&path), &["rev-parse", "HEAD"]),
+        git(repo.path(), &["rev-parse", "master"])
+    );
+
+    // -b still wins
+    let output = wt(repo.path(), &["new", "other", "-b", "main", "--print-path"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_new_no_migrate_refuses_current_branch() {
+    let repo = setup_git_repo();
+    git(repo.path(), &["checkout", "-b", "feature"]);
+    std::fs::write(repo.path().join("README.md"), "# Edited\n").unwrap();
+
+    let output = wt(repo.path(), &["new", "--no-migrate", "--print-path"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("can't check a branch out twice"),
+        "{}",
+        stderr
+    );
+
+    // Nothing was stashed or switched
+    assert_eq!(git(repo.path(), &["branch", "--show-current"]), "feature");
+    assert_eq!(git(repo.path(), &["stash", "list"]), "");
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("README.md")).unwrap(),
+        "# Edited\n"
+    );
+    assert!(!repo.path().join(".worktrees/feature").exists());
+}
+
+#[test]
+fn test_new_no_migrate_leaves_current_branch_alone() {
+    let repo = setup_git_repo();
+    git(repo.path(), &["branch", "other"]);
+    git(repo.path(), &["checkout", "-b", "feature"]);
+    std::fs::write(repo.path().join("README.md"), "# Edited\n").unwrap();
+
+    let output = wt(
+        repo.path(),
+        &["new", "other", "--no-migrate", "--print-path"],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert!(repo.path().join(".worktrees/other").exists());
+    assert_eq!(git(repo.path(), &["branch", "--show-current"]), "feature");
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("README.md")).unwrap(),
+        "# Edited\n"
+    );
+}
+
+#[test]
+fn test_new_from_stash_applies_and_keeps_stash() {
+    let repo = setup_git_repo();
+    std::fs::write(repo.path().join("README.md"), "# Stashed\n").unwrap();
+    git(repo.path(), &["stash", "push", "-m", "experiment"]);
+
+    let output = wt(
+        repo.path(),
+        &[
+            "new",
+            "experiment",
+            "--from-stash",
+            "stash@{0}",
+            "--print-path",
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    assert_eq!(
+        std::fs::read_to_string(Path::new(&path).join("README.md")).unwrap(),
+        "# Stashed\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("README.md")).unwrap(),
+        "# Test Repo\n"
+    );
+    assert!(git(repo.path(), &["stash", "list"]).contains("experiment"));
+}
+
+#[test]
+fn test_new_from_missing_stash_creates_nothing() {
+    let repo = setup_git_repo();
+
+    let output = wt(
+        repo.path(),
+        &[
+            "new",
+            "experiment",
+            "--from-stash",
+            "stash@{3}",
+            "--print-path",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No stash 'stash@{3}'"));
+    assert!(!repo.path().join(".worktrees/experiment").exists());
+}
+
+#[test]
+fn test_new_panes_requires_tmux() {
+    let repo = setup_git_repo();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+        .args(["new", "feature", "--panes", "2"])
+        .current_dir(repo.path())
+        .env_remove("WT_ACTIVE")
+        .env_remove("TMUX")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--panes needs tmux"), "{}", stderr);
+    assert!(!repo.path().join(".worktrees/feature").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_new_runs_post_create_hook_in_worktree() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = setup_git_repo();
+    let hooks = repo.path().join(".wt").join("hooks");
+    std::fs::create_dir_all(&hooks).unwrap();
+    let hook = hooks.join("post-create");
+    std::fs::write(&hook, "#!/bin/sh\necho hook output\ntouch from-hook\n").unwrap();
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = wt(repo.path(), &["new", "feature/hooked", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+
+    // Hook output must not end up in the path a shell wrapper cd's into
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let path = Path::new(stdout.trim());
+    assert!(path.join("from-hook").exists(), "{}", stdout);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("hook output"));
+}
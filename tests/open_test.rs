@@ -0,0 +1,43 @@
+mod common;
+use common::{setup_git_repo, wt};
+
+#[test]
+fn test_open_runs_editor_in_worktree() {
+    let repo = setup_git_repo();
+    std::fs::write(
+        repo.path().join(".wt.toml"),
+        "[session]\neditor_cmd = \"touch opened\"\n",
+    )
+    .unwrap();
+
+    let output = wt(repo.path(), &["new", "feature/x", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+
+    let output = wt(repo.path(), &["open", "feature/x"]);
+    assert!(output.status.success(), "wt open failed: {:?}", output);
+    assert!(repo
+        .path()
+        .join(".worktrees")
+        .join("feature--x")
+        .join("opened")
+        .exists());
+}
+
+#[test]
+fn test_open_exits_with_editor_status() {
+    let repo = setup_git_repo();
+    std::fs::write(
+        repo.path().join(".wt.toml"),
+        "[session]\neditor_cmd = \"exit 3\"\n",
+    )
+    .unwrap();
+
+    let output = wt(repo.path(), &["new", "feature-y", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+
+    let output = wt(repo.path(), &["open", "feature-y"]);
+    assert_eq!(output.status.code(), Some(3));
+
+    let output = wt(repo.path(), &["open", "missing"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,187 @@
+use std::process::Command;
+use tempfile::TempDir;
+
+mod common;
+use common::{setup_git_repo, wt};
+
+#[test]
+fn test_rm_all_match_removes_only_matching_worktrees() {
+    let repo = setup_git_repo();
+
+    for name in ["spike/a", "spike/b", "feature/c"] {
+        let output = wt(repo.path(), &["new", name, "--print-path"]);
+        assert!(
+            output.status.success(),
+            "wt new {} failed: {:?}",
+            name,
+            output
+        );
+    }
+
+    let output = wt(repo.path(), &["rm", "--all", "--match", "spike/*", "--yes"]);
+    assert!(output.status.success(), "wt rm failed: {:?}", output);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Matched 2 worktree(s), skipped 1"));
+
+    let worktrees = repo.path().join(".worktrees");
+    assert!(!worktrees.join("spike--a").exists());
+    assert!(!worktrees.join("spike--b").exists());
+    assert!(worktrees.join("feature--c").exists());
+}
+
+#[test]
+fn test_rm_all_requires_yes_when_not_interactive() {
+    let repo = setup_git_repo();
+
+    let output = wt(repo.path(), &["new", "spike/a", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+
+    let output = wt(repo.path(), &["rm", "--all"]);
+    assert!(!output.status.success());
+    assert!(repo.path().join(".worktrees").join("spike--a").exists());
+}
+
+#[test]
+fn test_rm_refuses_unmerged_branch_without_force() {
+    let repo = setup_git_repo();
+
+    let output = wt(repo.path(), &["new", "feature/x", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let worktree = repo.path().join(".worktrees").join("feature--x");
+
+    let output = Command::new("git")
+        .args(["commit", "--allow-empty", "-m", "wip"])
+        .current_dir(&worktree)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git commit failed: {:?}", output);
+
+    let output = wt(repo.path(), &["rm", "feature/x"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("has 1 commit(s) not on 'main'"),
+        "{}",
+        stderr
+    );
+    assert!(worktree.exists());
+
+    let output = wt(repo.path(), &["rm", "feature/x", "--force"]);
+    assert!(
+        output.status.success(),
+        "wt rm --force failed: {:?}",
+        output
+    );
+    assert!(!worktree.exists());
+}
+
+#[test]
+fn test_rm_merged_branch_needs_no_force() {
+    let repo = setup_git_repo();
+
+    let output = wt(repo.path(), &["new", "feature/y", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+
+    let output = wt(repo.path(), &["rm", "feature/y"]);
+    assert!(output.status.success(), "wt rm failed: {:?}", output);
+    assert!(!repo.path().join(".worktrees").join("feature--y").exists());
+}
+
+#[test]
+fn test_rm_leaves_hand_made_worktrees_alone() {
+    let repo = setup_git_repo();
+    let elsewhere = TempDir::new().unwrap();
+    let hand_made = elsewhere.path().join("by-hand");
+
+    let output = Command::new("git")
+        .args(["worktree", "add", "-b", "by-hand"])
+        .arg(&hand_made)
+        .current_dir(repo.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git worktree add failed: {:?}",
+        output
+    );
+
+    let output = wt(repo.path(), &["rm", "by-hand", "--force"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("won't remove it"));
+
+    let output = wt(repo.path(), &["rm", "--all", "--yes"]);
+    assert!(output.status.success(), "wt rm --all failed: {:?}", output);
+    assert!(hand_made.exists());
+}
+
+#[test]
+fn test_rm_runs_pre_remove_hook_before_deleting() {
+    let repo = setup_git_repo();
+    std::fs::write(
+        repo.path().join(".wt.toml"),
+        "[hooks]\npre_remove = 'test -d \"$WT_PATH\" && pwd > ../../hook-ran && echo \"$WT_NAME\" >> ../../hook-ran && echo from-hook'\n",
+    )
+    .unwrap();
+
+    let output = wt(repo.path(), &["new", "feature/z", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let worktree = repo.path().join(".worktrees").join("feature--z");
+
+    let output = wt(repo.path(), &["rm", "feature/z"]);
+    assert!(output.status.success(), "wt rm failed: {:?}", output);
+    assert!(!worktree.exists());
+    // Hook output stays off stdout, like `.wt/hooks/` scripts
+    assert!(output.stdout.is_empty(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("from-hook"));
+
+    let marker = std::fs::read_to_string(repo.path().join("hook-ran")).unwrap();
+    let lines: Vec<&str> = marker.lines().collect();
+    assert!(lines[0].ends_with(".worktrees/feature--z"), "{}", marker);
+    assert_eq!(lines[1], "feature/z");
+}
+
+#[test]
+fn test_rm_failing_pre_remove_hook_aborts_without_force() {
+    let repo = setup_git_repo();
+    std::fs::write(
+        repo.path().join(".wt.toml"),
+        "[hooks]\npre_remove = 'exit 3'\n",
+    )
+    .unwrap();
+
+    let output = wt(repo.path(), &["new", "feature/h", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let worktree = repo.path().join(".worktrees").join("feature--h");
+
+    let output = wt(repo.path(), &["rm", "feature/h"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("pre_remove hook"), "{}", stderr);
+    assert!(worktree.exists());
+
+    let output = wt(repo.path(), &["rm", "feature/h", "--force"]);
+    assert!(
+        output.status.success(),
+        "wt rm --force failed: {:?}",
+        output
+    );
+    assert!(!worktree.exists());
+}
+
+#[test]
+fn test_rm_keeps_branch() {
+    let repo = setup_git_repo();
+
+    let output = wt(repo.path(), &["new", "feature/keep", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+
+    let output = wt(repo.path(), &["rm", "feature/keep"]);
+    assert!(output.status.success(), "wt rm failed: {:?}", output);
+    let output = Command::new("git")
+        .args(["show-ref", "--verify", "--quiet", "refs/heads/feature/keep"])
+        .current_dir(repo.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
@@ -0,0 +1,118 @@
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+mod common;
+use common::{setup_git_repo, wt};
+
+fn commit_file(dir: &Path, file: &str) {
+    std::fs::write(dir.join(file), file).unwrap();
+    for args in [&["add", "."][..], &["commit", "-m", file][..]] {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {:?}",
+            args,
+            output
+        );
+    }
+}
+
+fn head(dir: &Path) -> String {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn test_sync_fast_forwards_from_base() {
+    let repo = setup_git_repo();
+    let output = wt(repo.path(), &["new", "feature", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let worktree = std::path::PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+
+    commit_file(repo.path(), "on-main.txt");
+
+    let output = wt(repo.path(), &["sync", "feature"]);
+    assert!(output.status.success(), "wt sync failed: {:?}", output);
+    assert_eq!(head(&worktree), head(repo.path()));
+
+    let output = wt(repo.path(), &["sync", "feature"]);
+    assert!(output.status.success(), "wt sync failed: {:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("up to date"));
+}
+
+#[test]
+fn test_sync_refuses_diverged_branch_unless_rebase() {
+    let repo = setup_git_repo();
+    let output = wt(repo.path(), &["new", "feature", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let worktree = std::path::PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+
+    commit_file(&worktree, "on-feature.txt");
+    commit_file(repo.path(), "on-main.txt");
+    let before = head(&worktree);
+
+    let output = wt(repo.path(), &["sync", "feature"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 ahead, 1 behind"));
+    assert_eq!(head(&worktree), before);
+
+    let output = wt(repo.path(), &["sync", "feature", "--rebase"]);
+    assert!(
+        output.status.success(),
+        "wt sync --rebase failed: {:?}",
+        output
+    );
+    assert!(worktree.join("on-main.txt").exists());
+    assert!(worktree.join("on-feature.txt").exists());
+}
+
+#[test]
+fn test_sync_pulls_from_base_upstream() {
+    let repo = setup_git_repo();
+    let remote = TempDir::new().unwrap();
+    let other = TempDir::new().unwrap();
+    let git = |dir: &Path, args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {:?}",
+            args,
+            output
+        );
+    };
+
+    git(remote.path(), &["init", "--bare", "-b", "main"]);
+    let remote_url = remote.path().to_str().unwrap();
+    git(repo.path(), &["remote", "add", "origin", remote_url]);
+    git(repo.path(), &["push", "-u", "origin", "main"]);
+
+    let output = wt(repo.path(), &["new", "feature", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let worktree = std::path::PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+
+    // Someone else pushes to main; the local main never sees it
+    git(other.path(), &["clone", remote_url, "."]);
+    git(other.path(), &["config", "user.email", "test@example.com"]);
+    git(other.path(), &["config", "user.name", "Test User"]);
+    commit_file(other.path(), "upstream.txt");
+    git(other.path(), &["push", "origin", "main"]);
+
+    let output = wt(repo.path(), &["sync", "feature"]);
+    assert!(output.status.success(), "wt sync failed: {:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("origin/main"));
+    assert!(worktree.join("upstream.txt").exists());
+    assert_eq!(head(&worktree), head(other.path()));
+}
@@ -16,6 +16,17 @@ fn setup_test_repo() -> (TempDir, PathBuf) {
         .output()
         .expect("Failed to init git repo");
 
+    for (key, value) in [
+        ("user.email", "test@example.com"),
+        ("user.name", "Test User"),
+    ] {
+        Command::new("git")
+            .args(["config", key, value])
+            .current_dir(&repo_path)
+            .output()
+            .expect("Failed to configure git");
+    }
+
     Command::new("git")
         .args(["commit", "--allow-empty", "-m", "init"])
         .current_dir(&repo_path)
@@ -61,8 +72,15 @@ fn test_tmux_session_lifecycle() {
     let windows = tmux.list_windows().unwrap();
     assert_eq!(windows.len(), 2);
 
+    // Test window rename
+    tmux.rename_window("second-window", "renamed-window")
+        .unwrap();
+    let windows = tmux.list_windows().unwrap();
+    assert!(windows.iter().any(|window| window.name == "renamed-window"));
+    assert!(!windows.iter().any(|window| window.name == "second-window"));
+
     // Test window removal
-    tmux.kill_window("second-window").unwrap();
+    tmux.kill_window("renamed-window").unwrap();
     let windows = tmux.list_windows().unwrap();
     assert_eq!(windows.len(), 1);
 
@@ -87,7 +105,7 @@ fn test_tmux_pane_layout_2_panes() {
 
     let config = SessionConfig::default();
     tmux.create_session("test-window", &repo_path).unwrap();
-    tmux.setup_worktree_layout("test-window", &repo_path, 2, &config)
+    tmux.setup_worktree_layout("test-window", &repo_path, 2, None, &config)
         .unwrap();
 
     let windows = tmux.list_windows().unwrap();
@@ -97,6 +115,100 @@ fn test_tmux_pane_layout_2_panes() {
     kill_tmux_session(session_name);
 }
 
+#[test]
+#[ignore]
+fn test_tmux_pane_layout_main_pane_width() {
+    if !TmuxManager::is_available() {
+        eprintln!("tmux not available, skipping test");
+        return;
+    }
+
+    let session_name = "wt-test-layout-width";
+    let tmux = TmuxManager::new(session_name);
+    let (_temp_dir, repo_path) = setup_test_repo();
+
+    // Cleanup any existing test session
+    kill_tmux_session(session_name);
+
+    let config = SessionConfig::default();
+    tmux.create_session("test-window", &repo_path).unwrap();
+    tmux.setup_worktree_layout("test-window", &repo_path, 2, Some("70%"), &config)
+        .unwrap();
+
+    let widths = Command::new("tmux")
+        .args([
+            "display-message",
+            "-p",
+            "-t",
+            &format!("{}:test-window.0", session_name),
+            "#{pane_width} #{window_width}",
+        ])
+        .output()
+        .unwrap();
+    let widths = String::from_utf8_lossy(&widths.stdout);
+    let (pane, window) = widths.trim().split_once(' ').unwrap();
+    let (pane, window): (u32, u32) = (pane.parse().unwrap(), window.parse().unwrap());
+    assert!(pane * 10 >= window * 6, "{} of {}", pane, window);
+
+    assert!(tmux
+        .setup_worktree_layout("test-window", &repo_path, 2, Some("wide%"), &config)
+        .is_err());
+
+    // Without a layout, main_pane_percent sizes the agent pane
+    let config = SessionConfig {
+        main_pane_percent: 70,
+        ..SessionConfig::default()
+    };
+    tmux.create_window("percent-window", &repo_path).unwrap();
+    tmux.setup_worktree_layout("percent-window", &repo_path, 2, None, &config)
+        .unwrap();
+    let widths = Command::new("tmux")
+        .args([
+            "display-message",
+            "-p",
+            "-t",
+            &format!("{}:percent-window.0", session_name),
+            "#{pane_width} #{window_width}",
+        ])
+        .output()
+        .unwrap();
+    let widths = String::from_utf8_lossy(&widths.stdout);
+    let (pane, window) = widths.trim().split_once(' ').unwrap();
+    let (pane, window): (u32, u32) = (pane.parse().unwrap(), window.parse().unwrap());
+    assert!(pane * 10 >= window * 6, "{} of {}", pane, window);
+
+    // Cleanup
+    kill_tmux_session(session_name);
+}
+
+#[test]
+#[ignore]
+fn test_tmux_split_returns_new_pane_index() {
+    if !TmuxManager::is_available() {
+        eprintln!("tmux not available, skipping test");
+        return;
+    }
+
+    let session_name = "wt-test-split";
+    let tmux = TmuxManager::new(session_name);
+    let (_temp_dir, repo_path) = setup_test_repo();
+
+    // Cleanup any existing test session
+    kill_tmux_session(session_name);
+
+    tmux.create_session("host", &repo_path).unwrap();
+    let first = tmux.split_window_horizontal("host", &repo_path).unwrap();
+    let second = tmux.split_window_vertical("host", &repo_path).unwrap();
+    assert_ne!(first, second);
+    tmux.send_keys("host", second, "echo split-ok").unwrap();
+
+    let windows = tmux.list_windows().unwrap();
+    assert_eq!(windows[0].pane_count, 3);
+
+    // Cleanup
+    kill_tmux_session(session_name);
+}
+
 #[test]
 #[ignore]
 fn test_tmux_pane_layout_3_panes() {
@@ -114,7 +226,7 @@ fn test_tmux_pane_layout_3_panes() {
 
     let config = SessionConfig::default();
     tmux.create_session("test-window", &repo_path).unwrap();
-    tmux.setup_worktree_layout("test-window", &repo_path, 3, &config)
+    tmux.setup_worktree_layout("test-window", &repo_path, 3, None, &config)
         .unwrap();
 
     let windows = tmux.list_windows().unwrap();
@@ -151,6 +263,127 @@ fn test_tmux_create_window_uses_next_free_index() {
     kill_tmux_session(&session_name);
 }
 
+#[test]
+#[ignore]
+fn test_session_add_several_names_with_no_attach() {
+    if !TmuxManager::is_available() {
+        eprintln!("tmux not available, skipping test");
+        return;
+    }
+
+    let sessions = ["wt-test-noattach-a", "wt-test-noattach-b"];
+    let (_temp_dir, repo_path) = setup_test_repo();
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        repo_path.join(".wt.toml"),
+        "[session]\nsession_prefix = \"wt-test-noattach-\"\n",
+    )
+    .unwrap();
+
+    // Cleanup any existing test sessions
+    for session_name in sessions {
+        kill_tmux_session(session_name);
+    }
+
+    // Attaching or switching without a terminal would fail, so success
+    // means it never tried
+    let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+        .args([
+            "session",
+            "--mode",
+            "windows",
+            "add",
+            "a",
+            "b",
+            "--no-attach",
+        ])
+        .current_dir(&repo_path)
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .env("XDG_DATA_HOME", home.path().join("data"))
+        .env_remove("WT_ACTIVE")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    for session_name in sessions {
+        assert!(TmuxManager::new(session_name).session_exists().unwrap());
+    }
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("run 'wt session --mode windows' to attach later"));
+
+    // Cleanup
+    for session_name in sessions {
+        kill_tmux_session(session_name);
+    }
+}
+
+#[test]
+#[ignore]
+fn test_session_add_agent_override() {
+    if !TmuxManager::is_available() {
+        eprintln!("tmux not available, skipping test");
+        return;
+    }
+
+    let session_name = "wt-test-agent-override-x";
+    let (_temp_dir, repo_path) = setup_test_repo();
+    let home = TempDir::new().unwrap();
+    std::fs::write(
+        repo_path.join(".wt.toml"),
+        "[session]\nsession_prefix = \"wt-test-agent-override-\"\nagent_cmd = \"echo configured-agent\"\n",
+    )
+    .unwrap();
+
+    // Cleanup any existing test session
+    kill_tmux_session(session_name);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+        .args([
+            "session",
+            "--mode",
+            "windows",
+            "add",
+            "x",
+            "--panes",
+            "1",
+            "--agent",
+            "echo override-agent",
+            "--no-attach",
+        ])
+        .current_dir(&repo_path)
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .env("XDG_DATA_HOME", home.path().join("data"))
+        .env_remove("WT_ACTIVE")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let tmux = TmuxManager::new(session_name);
+    let mut contents = String::new();
+    for _ in 0..20 {
+        contents = tmux.capture_pane("agent", 0).unwrap();
+        if contents.contains("override-agent\n") {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert!(contents.contains("echo override-agent"), "{}", contents);
+    assert!(!contents.contains("configured-agent"), "{}", contents);
+
+    // Cleanup
+    kill_tmux_session(session_name);
+}
+
 #[test]
 fn test_session_state_persistence() {
     let mut state = SessionState::new("test-session");
@@ -187,10 +420,171 @@ fn test_config_effective_panes() {
     assert_eq!(config.effective_panes(None), 2);
 
     // Override with valid values
+    assert_eq!(config.effective_panes(Some(1)), 1);
     assert_eq!(config.effective_panes(Some(2)), 2);
     assert_eq!(config.effective_panes(Some(3)), 3);
 
     // Override clamped to valid range
-    assert_eq!(config.effective_panes(Some(1)), 2);
+    assert_eq!(config.effective_panes(Some(0)), 1);
     assert_eq!(config.effective_panes(Some(4)), 3);
 }
+
+#[test]
+#[ignore]
+fn test_panes_session_refuses_another_repo() {
+    if !TmuxManager::is_available() {
+        eprintln!("tmux not available, skipping test");
+        return;
+    }
+
+    // A private tmux server, since the panes session is always named `wt`
+    let tmux_dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    let (_first_dir, first) = setup_test_repo();
+    let (_second_dir, second) = setup_test_repo();
+    let wt = |repo: &PathBuf, name: &str| {
+        Command::new(env!("CARGO_BIN_EXE_wt"))
+            .args(["session", "--mode", "panes", "add", name, "--no-attach"])
+            .current_dir(repo)
+            .env("TMUX_TMPDIR", tmux_dir.path())
+            .env_remove("TMUX")
+            .env("HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path().join("config"))
+            .env("XDG_DATA_HOME", home.path().join("data"))
+            .env_remove("WT_ACTIVE")
+            .output()
+            .unwrap()
+    };
+
+    let output = wt(&first, "a");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = wt(&second, "b");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("belongs to"), "{}", stderr);
+    assert!(!second.join(".worktrees").join("b").exists());
+
+    let output = wt(&first, "c");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let _ = Command::new("tmux")
+        .args(["kill-server"])
+        .env("TMUX_TMPDIR", tmux_dir.path())
+        .env_remove("TMUX")
+        .output();
+}
+
+#[test]
+#[ignore]
+fn test_session_watch_json_stops_on_closed_pipe() {
+    use std::io::BufRead;
+    use std::process::Stdio;
+
+    if !TmuxManager::is_available() {
+        eprintln!("tmux not available, skipping test");
+        return;
+    }
+
+    // A private tmux server, since the panes session is always named `wt`
+    let tmux_dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    let (_temp_dir, repo_path) = setup_test_repo();
+    let wt = |args: &[&str]| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_wt"));
+        command
+            .args(["session", "--mode", "panes"])
+            .args(args)
+            .current_dir(&repo_path)
+            .env("TMUX_TMPDIR", tmux_dir.path())
+            .env_remove("TMUX")
+            .env("HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path().join("config"))
+            .env("XDG_DATA_HOME", home.path().join("data"))
+            .env_remove("WT_ACTIVE");
+        command
+    };
+
+    let output = wt(&["add", "a", "--no-attach"]).output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Like `wt session watch --json | head -1`: read one snapshot, then
+    // close the pipe
+    let mut child = wt(&["watch", "--json", "-i", "1"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut line = String::new();
+    std::io::BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    assert!(line.starts_with('['), "{}", line);
+    assert!(child.wait().unwrap().success());
+
+    let _ = Command::new("tmux")
+        .args(["kill-server"])
+        .env("TMUX_TMPDIR", tmux_dir.path())
+        .env_remove("TMUX")
+        .output();
+}
+
+#[test]
+#[ignore]
+fn test_session_state_records_window_indices() {
+    if !TmuxManager::is_available() {
+        eprintln!("tmux not available, skipping test");
+        return;
+    }
+
+    // A private tmux server, since the panes session is always named `wt`
+    let tmux_dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    let (_temp_dir, repo_path) = setup_test_repo();
+    for name in ["a", "b", "c"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+            .args(["session", "--mode", "panes", "add", name, "--no-attach"])
+            .current_dir(&repo_path)
+            .env("TMUX_TMPDIR", tmux_dir.path())
+            .env_remove("TMUX")
+            .env("HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path().join("config"))
+            .env("XDG_DATA_HOME", home.path().join("data"))
+            .env_remove("WT_ACTIVE")
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let sessions = std::fs::read_dir(home.path().join("data").join("wt").join("sessions"))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let state: SessionState =
+        serde_json::from_str(&std::fs::read_to_string(sessions).unwrap()).unwrap();
+    let index = |name: &str| state.get_worktree(name).unwrap().window_index;
+    assert!(index("a") < index("b") && index("b") < index("c"));
+
+    let _ = Command::new("tmux")
+        .args(["kill-server"])
+        .env("TMUX_TMPDIR", tmux_dir.path())
+        .env_remove("TMUX")
+        .output();
+}
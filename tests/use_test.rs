@@ -0,0 +1,29 @@
+mod common;
+use common::{setup_git_repo, wt};
+
+#[test]
+fn test_use_print_path_prints_existing_worktree() {
+    let repo = setup_git_repo();
+
+    let output = wt(repo.path(), &["new", "feature/x", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let created = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let output = wt(repo.path(), &["use", "feature/x", "--print-path"]);
+    assert!(output.status.success(), "wt use failed: {:?}", output);
+    let resolved = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    assert_eq!(
+        std::fs::canonicalize(resolved).unwrap(),
+        std::fs::canonicalize(created).unwrap()
+    );
+}
+
+#[test]
+fn test_use_print_path_fails_for_missing_worktree() {
+    let repo = setup_git_repo();
+
+    let output = wt(repo.path(), &["use", "missing", "--print-path"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
@@ -2,78 +2,136 @@ use std::fs;
 use std::process::Command;
 use tempfile::TempDir;
 
-fn setup_git_repo() -> TempDir {
-    let temp_dir = TempDir::new().unwrap();
-    let repo_path = temp_dir.path();
+mod common;
+use common::setup_git_repo;
 
-    Command::new("git")
-        .args(["init", "-b", "main"])
-        .current_dir(repo_path)
-        .output()
-        .unwrap();
+#[test]
+fn test_which_returns_main_in_main_repo() {
+    use wt::worktree_manager::get_current_worktree_name;
 
-    Command::new("git")
-        .args(["config", "user.email", "test@example.com"])
-        .current_dir(repo_path)
-        .output()
-        .unwrap();
+    let repo = setup_git_repo();
+    let result = get_current_worktree_name(repo.path()).unwrap();
+    assert_eq!(result, "main");
+}
 
-    Command::new("git")
-        .args(["config", "user.name", "Test User"])
-        .current_dir(repo_path)
-        .output()
-        .unwrap();
+#[test]
+fn test_which_returns_worktree_name_in_worktree() {
+    use wt::worktree_manager::get_current_worktree_name;
 
-    std::fs::write(repo_path.join("README.md"), "# Test Repo\n").unwrap();
+    let repo = setup_git_repo();
+    let worktree_dir = TempDir::new().unwrap();
+    let worktree_path = worktree_dir.path().join("feature-xyz");
 
-    Command::new("git")
-        .args(["add", "."])
-        .current_dir(repo_path)
+    let output = Command::new("git")
+        .args(["worktree", "add", "-b", "feature-xyz"])
+        .arg(&worktree_path)
+        .arg("main")
+        .current_dir(repo.path())
         .output()
         .unwrap();
 
-    Command::new("git")
-        .args(["commit", "-m", "Initial commit"])
-        .current_dir(repo_path)
-        .output()
-        .unwrap();
+    assert!(
+        output.status.success(),
+        "Failed to create worktree: {:?}",
+        output
+    );
 
-    temp_dir
+    let result = get_current_worktree_name(&worktree_path).unwrap();
+    assert_eq!(result, "feature-xyz");
 }
 
 #[test]
-fn test_which_returns_main_in_main_repo() {
+fn test_which_decodes_worktree_in_custom_dir() {
     use wt::worktree_manager::get_current_worktree_name;
 
     let repo = setup_git_repo();
-    let result = get_current_worktree_name(repo.path()).unwrap();
+    let worktree_path = repo.path().join("my-trees").join("feat--x");
+
+    let output = Command::new("git")
+        .args(["worktree", "add", "-b", "feat/x"])
+        .arg(&worktree_path)
+        .arg("main")
+        .current_dir(repo.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Failed to create worktree: {:?}",
+        output
+    );
+
+    let result = get_current_worktree_name(&worktree_path).unwrap();
+    assert_eq!(result, "feat/x");
+    let result = get_current_worktree_name(&worktree_path.join("..")).unwrap();
     assert_eq!(result, "main");
 }
 
 #[test]
-fn test_which_returns_worktree_name_in_worktree() {
+fn test_which_returns_main_in_submodule() {
     use wt::worktree_manager::get_current_worktree_name;
 
+    let sub = setup_git_repo();
     let repo = setup_git_repo();
-    let worktree_dir = TempDir::new().unwrap();
-    let worktree_path = worktree_dir.path().join("feature-xyz");
 
     let output = Command::new("git")
-        .args(["worktree", "add", "-b", "feature-xyz"])
+        .args(["-c", "protocol.file.allow=always", "submodule", "add"])
+        .arg(sub.path())
+        .arg("vendor/sub")
+        .current_dir(repo.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Failed to add submodule: {:?}",
+        output
+    );
+
+    // The submodule's git dir lives under the superproject's `.git/modules`
+    let result = get_current_worktree_name(&repo.path().join("vendor/sub")).unwrap();
+    assert_eq!(result, "main");
+}
+
+#[test]
+fn test_which_path_and_branch_flags() {
+    let repo = setup_git_repo();
+    let wt = |dir: &std::path::Path, args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+            .arg("which")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "wt which failed: {:?}", output);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    let worktree_path = repo.path().join(".worktrees").join("feature-xyz");
+    let output = Command::new("git")
+        .args(["worktree", "add", "-b", "feature/xyz"])
         .arg(&worktree_path)
         .arg("main")
         .current_dir(repo.path())
         .output()
         .unwrap();
-
     assert!(
         output.status.success(),
         "Failed to create worktree: {:?}",
         output
     );
 
-    let result = get_current_worktree_name(&worktree_path).unwrap();
-    assert_eq!(result, "feature-xyz");
+    let canonical = |path: &str| fs::canonicalize(path).unwrap();
+    assert_eq!(
+        canonical(&wt(&worktree_path, &["--path"])),
+        fs::canonicalize(&worktree_path).unwrap()
+    );
+    assert_eq!(wt(&worktree_path, &["--branch"]), "feature/xyz");
+
+    assert_eq!(
+        canonical(&wt(repo.path(), &["--path"])),
+        fs::canonicalize(repo.path()).unwrap()
+    );
+    assert_eq!(wt(repo.path(), &["--branch"]), "main");
+    assert_eq!(wt(repo.path(), &[]), "main");
 }
 
 #[test]
@@ -152,12 +210,30 @@ fn test_ensure_worktrees_in_gitignore_idempotent() {
     assert_eq!(count, 1);
 }
 
+#[test]
+fn test_ensure_worktrees_in_gitignore_skips_dir_outside_repo() {
+    use wt::worktree_manager::ensure_worktrees_in_gitignore;
+
+    let repo = setup_git_repo();
+    let external = TempDir::new().unwrap();
+    let gitignore_path = repo.path().join(".gitignore");
+
+    ensure_worktrees_in_gitignore(repo.path(), external.path()).unwrap();
+    assert!(!gitignore_path.exists());
+
+    fs::write(&gitignore_path, "node_modules\n").unwrap();
+    ensure_worktrees_in_gitignore(repo.path(), &repo.path().join("../external-trees")).unwrap();
+
+    let content = fs::read_to_string(&gitignore_path).unwrap();
+    assert_eq!(content, "node_modules\n");
+}
+
 #[test]
 fn test_check_not_in_worktree_allows_normal_path() {
     use wt::worktree_manager::check_not_in_worktree;
 
     let temp_dir = TempDir::new().unwrap();
-    let result = check_not_in_worktree(temp_dir.path());
+    let result = check_not_in_worktree(temp_dir.path(), &temp_dir.path().join(".worktrees"));
     assert!(result.is_ok());
 }
 
@@ -169,7 +245,41 @@ fn test_check_not_in_worktree_rejects_worktrees_dir() {
     let worktrees_path = temp_dir.path().join(".worktrees").join("some-worktree");
     fs::create_dir_all(&worktrees_path).unwrap();
 
-    let result = check_not_in_worktree(&worktrees_path);
+    let result = check_not_in_worktree(&worktrees_path, &worktrees_path.join(".worktrees"));
     assert!(result.is_err());
     assert!(result.unwrap_err().to_string().contains("nested"));
 }
+
+#[test]
+fn test_check_not_in_worktree_custom_dir() {
+    use wt::worktree_manager::check_not_in_worktree;
+
+    let temp_dir = TempDir::new().unwrap();
+    let worktree_path = temp_dir.path().join("my-trees").join("some-worktree");
+    fs::create_dir_all(&worktree_path).unwrap();
+
+    let result = check_not_in_worktree(&worktree_path, &worktree_path.join("my-trees"));
+    assert!(result.unwrap_err().to_string().contains("nested"));
+
+    // `.worktrees` is just a name when another dir is configured
+    let other = temp_dir.path().join(".worktrees").join("some-worktree");
+    fs::create_dir_all(&other).unwrap();
+    assert!(check_not_in_worktree(&other, &other.join("my-trees")).is_ok());
+}
+
+#[test]
+fn test_check_not_in_worktree_absolute_dir() {
+    use wt::worktree_manager::check_not_in_worktree;
+
+    let temp_dir = TempDir::new().unwrap();
+    let trees = temp_dir.path().join("trees");
+    let worktree_path = trees.join("some-worktree");
+    fs::create_dir_all(&worktree_path).unwrap();
+
+    let result = check_not_in_worktree(&worktree_path, &trees);
+    assert!(result.unwrap_err().to_string().contains("nested"));
+
+    let repo = temp_dir.path().join("repo");
+    fs::create_dir_all(&repo).unwrap();
+    assert!(check_not_in_worktree(&repo, &trees).is_ok());
+}
@@ -0,0 +1,140 @@
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{setup_git_repo, wt};
+
+#[test]
+fn test_worktree_dir_from_config_and_flag_override() {
+    let repo = setup_git_repo();
+    std::fs::write(repo.path().join(".wt.toml"), "worktree_dir = \"trees\"\n").unwrap();
+
+    let output = wt(repo.path(), &["new", "from-config", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    assert!(repo.path().join("trees").join("from-config").exists());
+
+    let output = wt(
+        repo.path(),
+        &["-d", ".other", "new", "from-flag", "--print-path"],
+    );
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    assert!(repo.path().join(".other").join("from-flag").exists());
+    assert!(!repo.path().join("trees").join("from-flag").exists());
+}
+
+#[test]
+fn test_path_matches_created_worktree() {
+    let repo = setup_git_repo();
+
+    let output = wt(repo.path(), &["path", "feature/x"]);
+    assert!(output.status.success(), "wt path failed: {:?}", output);
+    let previewed = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert!(!std::path::Path::new(&previewed).exists());
+
+    let output = wt(repo.path(), &["new", "feature/x", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let created = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert_eq!(previewed, created);
+}
+
+#[test]
+fn test_repo_flag_targets_another_checkout() {
+    let repo = setup_git_repo();
+    let elsewhere = TempDir::new().unwrap();
+    let repo_arg = repo.path().to_str().unwrap();
+
+    let output = wt(
+        elsewhere.path(),
+        &["--repo", repo_arg, "new", "remote-task", "--print-path"],
+    );
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    assert!(repo.path().join(".worktrees").join("remote-task").exists());
+
+    let output = wt(elsewhere.path(), &["--repo", repo_arg, "which", "--branch"]);
+    assert!(output.status.success(), "wt which failed: {:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "main");
+
+    let not_a_repo = elsewhere.path().to_str().unwrap();
+    let output = wt(repo.path(), &["--repo", not_a_repo, "ls"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Not a git repository"));
+}
+
+#[test]
+fn test_new_inside_custom_worktree_dir_is_nested() {
+    let repo = setup_git_repo();
+
+    let output = wt(
+        repo.path(),
+        &["-d", "my-trees", "new", "outer", "--print-path"],
+    );
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let outer = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let output = wt(Path::new(&outer), &["-d", "my-trees", "new", "inner"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("nested"), "{}", stderr);
+    assert!(!Path::new(&outer).join("my-trees").exists());
+}
+
+#[test]
+fn test_ls_and_rm_from_inside_a_worktree() {
+    let repo = setup_git_repo();
+    for name in ["a", "b"] {
+        let output = wt(repo.path(), &["new", name, "--print-path"]);
+        assert!(output.status.success(), "wt new failed: {:?}", output);
+    }
+    let inside = repo.path().join(".worktrees").join("a");
+
+    let output = wt(&inside, &["ls", "--plain"]);
+    assert!(output.status.success(), "wt ls failed: {:?}", output);
+    let mut names: Vec<_> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["a", "b"]);
+
+    let output = wt(&inside, &["rm", "b", "--force"]);
+    assert!(output.status.success(), "wt rm failed: {:?}", output);
+    assert!(!repo.path().join(".worktrees").join("b").exists());
+
+    let output = wt(&inside, &["which"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "a");
+}
+
+#[test]
+fn test_path_in_submodule_uses_submodule_checkout() {
+    let sub = setup_git_repo();
+    let repo = setup_git_repo();
+    let output = std::process::Command::new("git")
+        .args(["-c", "protocol.file.allow=always", "submodule", "add"])
+        .arg(sub.path())
+        .arg("vendor/sub")
+        .current_dir(repo.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "submodule add failed: {:?}",
+        output
+    );
+    let checkout = repo.path().join("vendor/sub").canonicalize().unwrap();
+    let path = |dir: &Path, name: &str| {
+        let output = wt(dir, &["path", name]);
+        assert!(output.status.success(), "wt path failed: {:?}", output);
+        std::path::PathBuf::from(String::from_utf8_lossy(&output.stdout).trim())
+    };
+
+    assert_eq!(path(&checkout, "foo"), checkout.join(".worktrees/foo"));
+
+    // From one of the submodule's own worktrees too
+    let output = wt(&checkout, &["new", "foo", "--print-path"]);
+    assert!(output.status.success(), "wt new failed: {:?}", output);
+    let foo = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert_eq!(
+        path(Path::new(&foo), "bar"),
+        checkout.join(".worktrees/bar")
+    );
+}